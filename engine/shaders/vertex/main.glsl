@@ -6,6 +6,7 @@ layout (location = 2) in vec2 tex_cords_in;
 layout (location = 3) in float tex_id;
 layout (location = 4) in mat4 trans;
 
+uniform mat4 projection;
 
 out vec4 Color;
 out vec2 tex_cords_out;
@@ -16,7 +17,7 @@ void main()
 		Tex_id = tex_id;
     Color = targetColor;
 		tex_cords_out = tex_cords_in;
-    gl_Position = trans * vec4(position, 0.0, 1.0);
+    gl_Position = projection * trans * vec4(position, 0.0, 1.0);
 }
 
 
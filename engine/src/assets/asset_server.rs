@@ -0,0 +1,219 @@
+use std::{
+  collections::HashMap,
+  path::{Path, PathBuf},
+  sync::{
+    mpsc::{self, Receiver, Sender},
+    Arc, Condvar, Mutex,
+  },
+  thread,
+};
+
+use crate::gl_utils::{
+  gl_texture::{Texture, TextureOptions},
+  image_data::{ImageData, ImageError},
+};
+
+/// Group assets go into unless they're loaded with `load_texture_in`
+pub const DEFAULT_ASSET_GROUP: &str = "default";
+
+/// Most worker threads an `AssetServer` decodes on
+pub const MAX_ASSET_WORKERS: usize = 4;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// How far along the assets of a group are
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct LoadingProgress {
+  pub total: usize,
+  pub loaded: usize,
+  pub failed: usize,
+}
+
+impl LoadingProgress {
+  /// Whether every asset either loaded or failed
+  pub fn is_done(&self) -> bool {
+    self.loaded + self.failed >= self.total
+  }
+
+  /// Share of the assets that are done, from 0.0 to 1.0.
+  /// Groups without any assets are done.
+  pub fn get_fraction(&self) -> f32 {
+    if self.total == 0 {
+      return 1.0;
+    }
+    (self.loaded + self.failed) as f32 / self.total as f32
+  }
+}
+
+#[derive(Default)]
+struct AssetGroup {
+  progress: Mutex<LoadingProgress>,
+  finished: Condvar,
+}
+
+impl AssetGroup {
+  fn finish(&self, loaded: bool) {
+    let mut progress = self.progress.lock().unwrap();
+    if loaded {
+      progress.loaded += 1;
+    } else {
+      progress.failed += 1;
+    }
+    self.finished.notify_all();
+  }
+}
+
+/// Asset Server
+///
+/// Decodes images on worker threads so loading doesn't hitch the game.
+/// Textures are handed out right away and drawn as the placeholder until
+/// their image is decoded, then uploaded by the render backend on the main
+/// thread like any other texture.
+///
+/// Assets are loaded in named groups, e.g. one per level, whose progress
+/// can be shown on a loading screen.
+pub struct AssetServer {
+  asset_root: PathBuf,
+  placeholder: Texture,
+  jobs: Sender<Job>,
+  groups: HashMap<String, Arc<AssetGroup>>,
+  errors: Arc<Mutex<Vec<(String, ImageError)>>>,
+}
+
+impl AssetServer {
+  /// Asset server reading `images/{name}.png` files under `asset_root`,
+  /// usually `BowTie::get_asset_root`
+  pub fn new<P: Into<PathBuf>>(asset_root: P) -> AssetServer {
+    let workers = thread::available_parallelism()
+      .map_or(1, |count| count.get())
+      .min(MAX_ASSET_WORKERS);
+
+    let (jobs, receiver) = mpsc::channel::<Job>();
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..workers {
+      let receiver = receiver.clone();
+      thread::spawn(move || AssetServer::work(receiver));
+    }
+
+    AssetServer {
+      asset_root: asset_root.into(),
+      placeholder: Texture::none(),
+      jobs,
+      groups: HashMap::new(),
+      errors: Arc::new(Mutex::new(vec![])),
+    }
+  }
+
+  /// Runs jobs until the asset server is dropped
+  fn work(receiver: Arc<Mutex<Receiver<Job>>>) {
+    loop {
+      let job = receiver.lock().unwrap().recv();
+      match job {
+        Ok(job) => job(),
+        Err(_) => return,
+      }
+    }
+  }
+
+  pub fn get_asset_root(&self) -> &Path {
+    &self.asset_root
+  }
+
+  /// Texture drawn while images load, or in place of images that failed.
+  /// `Texture::none()` by default, which shows the sprite's color.
+  /// Only applies to textures loaded afterwards.
+  pub fn set_placeholder(&mut self, placeholder: Texture) {
+    self.placeholder = placeholder;
+  }
+
+  /// Starts loading `images/{image_name}.png` in the default group
+  pub fn load_texture(
+    &mut self,
+    image_name: &str,
+    options: TextureOptions,
+  ) -> Texture {
+    self.load_texture_in(DEFAULT_ASSET_GROUP, image_name, options)
+  }
+
+  /// Starts loading `images/{image_name}.png` in `group` and returns its
+  /// texture, which shows the placeholder until the image is decoded
+  pub fn load_texture_in(
+    &mut self,
+    group: &str,
+    image_name: &str,
+    options: TextureOptions,
+  ) -> Texture {
+    let texture =
+      Texture::pending(image_name, options, self.placeholder.to_owned());
+
+    let group = self.groups.entry(String::from(group)).or_default().clone();
+    group.progress.lock().unwrap().total += 1;
+
+    let location = texture.get_image_location(&self.asset_root);
+    let loading = texture.to_owned();
+    let errors = self.errors.clone();
+    let job: Job = Box::new(move || {
+      match ImageData::from_png_file(&location) {
+        Ok(image) => {
+          loading.finish_loading(image);
+          group.finish(true);
+        }
+        Err(error) => {
+          loading.fail_loading();
+          errors
+            .lock()
+            .unwrap()
+            .push((loading.image_name.to_owned(), error));
+          group.finish(false);
+        }
+      }
+    });
+
+    // Workers only stop once the server is dropped
+    self.jobs.send(job).unwrap();
+    texture
+  }
+
+  /// Progress of a group, groups nothing was loaded in are done
+  pub fn get_progress(&self, group: &str) -> LoadingProgress {
+    self
+      .groups
+      .get(group)
+      .map(|group| *group.progress.lock().unwrap())
+      .unwrap_or_default()
+  }
+
+  /// Progress of every group combined
+  pub fn get_total_progress(&self) -> LoadingProgress {
+    self
+      .groups
+      .values()
+      .fold(LoadingProgress::default(), |total, group| {
+        let progress = group.progress.lock().unwrap();
+        LoadingProgress {
+          total: total.total + progress.total,
+          loaded: total.loaded + progress.loaded,
+          failed: total.failed + progress.failed,
+        }
+      })
+  }
+
+  /// Blocks until every asset of the group is done loading
+  pub fn wait_for(&self, group: &str) -> LoadingProgress {
+    let group = match self.groups.get(group) {
+      Some(group) => group,
+      None => return LoadingProgress::default(),
+    };
+
+    let mut progress = group.progress.lock().unwrap();
+    while !progress.is_done() {
+      progress = group.finished.wait(progress).unwrap();
+    }
+    *progress
+  }
+
+  /// Image names and errors of the assets that failed since the last call
+  pub fn take_errors(&mut self) -> Vec<(String, ImageError)> {
+    std::mem::take(&mut *self.errors.lock().unwrap())
+  }
+}
@@ -0,0 +1,3 @@
+pub mod asset_server;
+
+mod test;
@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod asset_server_tests {
+  use std::{fs, path::PathBuf};
+
+  use crate::{
+    AssetServer, BowTie, ImageData, ImageError, LoadingProgress, Rectangle,
+    SoftwareBackend, Sprite, StandardEntity, Texture, TextureOptions, COLORS,
+  };
+
+  const RED: [u8; 4] = [255, 0, 0, 255];
+  const BLUE: [u8; 4] = [0, 0, 255, 255];
+
+  /// Asset root with a 1x1 png of every color under `images/`
+  fn asset_root(name: &str, images: &[(&str, [u8; 4])]) -> PathBuf {
+    let asset_root = std::env::temp_dir().join(name);
+    fs::create_dir_all(asset_root.join("images")).unwrap();
+    for (image_name, color) in images {
+      let location = asset_root.join(format!("images/{image_name}.png"));
+      ImageData::new(1, 1, color.to_vec())
+        .save_png(location.to_str().unwrap())
+        .unwrap();
+    }
+    asset_root
+  }
+
+  #[test]
+  fn reports_progress_per_group() {
+    let mut assets = AssetServer::new(asset_root(
+      "bowtie_asset_server_progress",
+      &[("red", RED), ("blue", BLUE)],
+    ));
+
+    let red = assets.load_texture_in("level", "red", TextureOptions::default());
+    assets.load_texture_in("level", "blue", TextureOptions::default());
+    let missing =
+      assets.load_texture_in("level", "missing", TextureOptions::default());
+    assets.load_texture("red", TextureOptions::default());
+
+    let level = assets.wait_for("level");
+    assert_eq!(
+      level,
+      LoadingProgress {
+        total: 3,
+        loaded: 2,
+        failed: 1,
+      }
+    );
+    assert_eq!(level.get_fraction(), 1.0);
+    assert!(!red.is_loading());
+    assert!(!missing.is_loading());
+
+    assert_eq!(assets.get_progress("unknown"), LoadingProgress::default());
+    assert!(assets.get_progress("unknown").is_done());
+
+    let errors = assets.take_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].0, "missing");
+    assert!(matches!(errors[0].1, ImageError::Io { .. }));
+    assert!(assets.take_errors().is_empty());
+  }
+
+  fn load_fullscreen(bowtie: &mut BowTie, texture: Texture) {
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(-1.0, 1.0, 2.0, 2.0, COLORS::White.into()),
+        texture,
+      ),
+      0.0,
+    ));
+  }
+
+  #[test]
+  fn draws_placeholder_until_loaded() {
+    let placeholder =
+      Texture::from_rgba("blue", 1, 1, BLUE.to_vec(), TextureOptions::default())
+        .unwrap();
+    let texture =
+      Texture::pending("red", TextureOptions::default(), placeholder);
+
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(2, 2)));
+    load_fullscreen(&mut bowtie, Texture::from(&texture));
+
+    bowtie.step(1);
+    assert!(texture.is_loading());
+    assert_eq!(bowtie.read_pixels().unwrap().get_pixel(0, 0), BLUE);
+
+    texture.finish_loading(ImageData::new(1, 1, RED.to_vec()));
+    bowtie.step(1);
+    assert!(!texture.is_loading());
+    assert_eq!(bowtie.read_pixels().unwrap().get_pixel(0, 0), RED);
+  }
+
+  #[test]
+  fn failed_images_keep_placeholder() {
+    let mut assets =
+      AssetServer::new(asset_root("bowtie_asset_server_placeholder", &[]));
+    assets.set_placeholder(
+      Texture::from_rgba("blue", 1, 1, BLUE.to_vec(), TextureOptions::default())
+        .unwrap(),
+    );
+
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(2, 2)));
+    load_fullscreen(
+      &mut bowtie,
+      assets.load_texture("missing", TextureOptions::default()),
+    );
+
+    assert_eq!(assets.wait_for("default").failed, 1);
+    bowtie.step(1);
+    assert_eq!(bowtie.read_pixels().unwrap().get_pixel(0, 0), BLUE);
+  }
+}
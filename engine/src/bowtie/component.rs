@@ -1,19 +1,65 @@
 use std::{
+  any::Any,
+  borrow::Cow,
   collections::HashMap,
   sync::{Arc, Mutex},
 };
 
-use crate::{general::value::Value, StandardEntity};
+use crate::{general::value::Value, Entity, StandardEntity};
+
+use super::{
+  entity::Component,
+  entity_store::EntityId,
+  message::{Message, MessageTarget},
+  query::{EntityQuery, EntityView},
+  time::Time,
+};
 
 pub type ComponentStore = HashMap<String, Value>;
-pub type ComponentFunction<'a> =
-  dyn Fn(&mut StandardEntity<'a>, Arc<Mutex<ComponentStore>>) -> () + 'a;
+pub type ComponentFunction<'a> = dyn Fn(
+    &mut StandardEntity<'a>,
+    Arc<Mutex<ComponentStore>>,
+    &ComponentContext,
+  ) -> ()
+  + 'a;
+/// Per entity state of a typed component, keyed by entity id
+pub type TypedStore<S> = Arc<Mutex<HashMap<EntityId, S>>>;
+pub type TypedComponentFunction<'a, S> =
+  dyn Fn(&mut StandardEntity<'a>, &mut S, &ComponentContext) -> () + 'a;
+pub type MessageFunction<'a> =
+  dyn Fn(&mut StandardEntity<'a>, Arc<Mutex<ComponentStore>>, &Message) + 'a;
+
+/// What a component can see of the game besides the entity it acts on
+pub struct ComponentContext {
+  time: Time,
+  entities: Vec<EntityView>,
+}
+
+impl ComponentContext {
+  pub fn new(time: Time, entities: Vec<EntityView>) -> ComponentContext {
+    ComponentContext { time, entities }
+  }
+
+  pub fn get_time(&self) -> &Time {
+    &self.time
+  }
+
+  /// Queries the loaded entities as they were when the update started,
+  /// so every component sees the same positions regardless of update order
+  pub fn query(&self) -> EntityQuery<'_> {
+    EntityQuery::new(Cow::Borrowed(&self.entities))
+  }
+}
 
 #[derive(Clone)]
 pub struct StandardComponent<'a> {
   component_function: Arc<ComponentFunction<'a>>,
   name: String,
   store: Arc<Mutex<ComponentStore>>,
+  message_handlers: Vec<(String, Arc<MessageFunction<'a>>)>,
+  typed_store: Option<Arc<dyn Any + Send + Sync>>,
+  register_entity: Option<Arc<dyn Fn(EntityId) + 'a>>,
+  forget_entity: Option<Arc<dyn Fn(EntityId) + 'a>>,
 }
 
 impl<'a> StandardComponent<'a> {
@@ -26,6 +72,156 @@ impl<'a> StandardComponent<'a> {
       component_function: f,
       name: String::from(name),
       store: Arc::new(Mutex::new(store_seed)),
+      message_handlers: vec![],
+      typed_store: None,
+      register_entity: None,
+      forget_entity: None,
+    }
+  }
+
+  /// Creates a component around an existing store, so the store can be
+  /// shared with the struct that built the component.
+  pub fn with_store(
+    f: Arc<ComponentFunction<'a>>,
+    name: &str,
+    store: Arc<Mutex<ComponentStore>>,
+  ) -> StandardComponent<'a> {
+    StandardComponent {
+      component_function: f,
+      name: String::from(name),
+      store,
+      message_handlers: vec![],
+      typed_store: None,
+      register_entity: None,
+      forget_entity: None,
+    }
+  }
+
+  /// Creates a component around an implementation of the `Component` trait.
+  /// Every message its `act` returns is sent to the entity it acted on.
+  pub fn from_component<C: Component<'a> + 'a>(
+    component: C,
+  ) -> StandardComponent<'a> {
+    let name = String::from(component.get_name());
+    let component = Mutex::new(component);
+    StandardComponent::new(
+      Arc::new(move |entity, _store, _context| {
+        let message = component.lock().unwrap().act(entity);
+        if let (Some(message), Some(id)) = (message, entity.get_id()) {
+          entity.send_message(MessageTarget::Entity(id), message);
+        }
+      }),
+      name.as_str(),
+      HashMap::new(),
+    )
+  }
+
+  /// Creates a component that keeps a `S` for every entity it acts on,
+  /// instead of sharing a `ComponentStore` of `Value`s.
+  ///
+  /// `f` gets the acting entity's state, which starts out as `S::default()`
+  /// when the entity is loaded and is dropped when it is unloaded.
+  pub fn typed<S: Default + Send + 'static>(
+    f: Arc<TypedComponentFunction<'a, S>>,
+    name: &str,
+  ) -> StandardComponent<'a> {
+    StandardComponent::with_typed_store(
+      f,
+      name,
+      Arc::new(Mutex::new(HashMap::new())),
+    )
+  }
+
+  /// Creates a typed component around an existing typed store, so the store
+  /// can be shared with the struct that built the component.
+  ///
+  /// While `f` runs, the acting entity's state is taken out of the store, so
+  /// `f` can lock the store to read every other entity's state.
+  pub fn with_typed_store<S: Default + Send + 'static>(
+    f: Arc<TypedComponentFunction<'a, S>>,
+    name: &str,
+    store: TypedStore<S>,
+  ) -> StandardComponent<'a> {
+    let states = store.clone();
+    let component_function: Arc<ComponentFunction<'a>> =
+      Arc::new(move |entity, _store, context| {
+        let id = entity.get_id();
+        let mut state = match id {
+          Some(id) => states.lock().unwrap().remove(&id).unwrap_or_default(),
+          None => S::default(),
+        };
+
+        f(entity, &mut state, context);
+
+        if let Some(id) = id {
+          states.lock().unwrap().insert(id, state);
+        }
+      });
+
+    let registered_states = store.clone();
+    let register_entity = Arc::new(move |id: EntityId| {
+      registered_states.lock().unwrap().entry(id).or_default();
+    });
+
+    let forgotten_states = store.clone();
+    let forget_entity = Arc::new(move |id: EntityId| {
+      forgotten_states.lock().unwrap().remove(&id);
+    });
+
+    StandardComponent {
+      component_function,
+      name: String::from(name),
+      store: Arc::new(Mutex::new(HashMap::new())),
+      message_handlers: vec![],
+      typed_store: Some(store),
+      register_entity: Some(register_entity),
+      forget_entity: Some(forget_entity),
+    }
+  }
+
+  /// Store of a component created with `typed`, `None` if the component is
+  /// untyped or `S` isn't its state type
+  pub fn get_typed_store<S: Send + 'static>(&self) -> Option<TypedStore<S>> {
+    self
+      .typed_store
+      .clone()?
+      .downcast::<Mutex<HashMap<EntityId, S>>>()
+      .ok()
+  }
+
+  /// Copy of the typed state the component keeps for the entity
+  pub fn get_state<S: Clone + Send + 'static>(&self, id: EntityId) -> Option<S> {
+    let store = self.get_typed_store::<S>()?;
+    let states = store.lock().unwrap();
+    states.get(&id).cloned()
+  }
+
+  /// Sets up what the store holds for a newly loaded entity, so the entity
+  /// has state before the component first runs on it.
+  /// Typed components give it `S::default()`.
+  pub fn add_entity(&self, id: EntityId) {
+    if let Some(register_entity) = &self.register_entity {
+      register_entity(id);
+    }
+  }
+
+  /// Drops everything the store holds for an unloaded entity.
+  ///
+  /// Typed components drop the entity's state. Otherwise per entity state is
+  /// expected to live under the entity's store key, either at the top level
+  /// of the store or inside a top level object.
+  pub fn remove_entity(&self, id: EntityId) {
+    if let Some(forget_entity) = &self.forget_entity {
+      forget_entity(id);
+    }
+
+    let key = id.to_string();
+    let mut store = self.store.lock().unwrap();
+    store.remove(&key);
+    for value in store.values_mut() {
+      if let Value::Object(objects) = value {
+        objects.remove(&key);
+      }
     }
   }
 
@@ -37,8 +233,41 @@ impl<'a> StandardComponent<'a> {
     self.name.to_owned()
   }
 
-  pub fn act(&self, entity: &mut StandardEntity<'a>) -> () {
+  pub fn act(
+    &self,
+    entity: &mut StandardEntity<'a>,
+    context: &ComponentContext,
+  ) -> () {
     let func = &self.component_function;
-    func(entity, self.store.clone());
+    func(entity, self.store.clone(), context);
+  }
+
+  /// Subscribes the component to messages of `message_type`.
+  /// The handler runs for every such message delivered to an entity that has
+  /// this component loaded.
+  ///
+  /// Subscribe before cloning the component onto entities, clones made
+  /// earlier do not pick up new handlers.
+  pub fn on_message(&mut self, message_type: &str, f: Arc<MessageFunction<'a>>) {
+    self.message_handlers.push((String::from(message_type), f));
+  }
+
+  pub fn subscribes_to(&self, message_type: &str) -> bool {
+    self
+      .message_handlers
+      .iter()
+      .any(|(subscribed_type, _)| subscribed_type == message_type)
+  }
+
+  pub fn handle_message(
+    &self,
+    entity: &mut StandardEntity<'a>,
+    message: &Message,
+  ) {
+    for (message_type, func) in self.message_handlers.iter() {
+      if *message_type == message.get_message_type() {
+        func(entity, self.store.clone(), message);
+      }
+    }
   }
 }
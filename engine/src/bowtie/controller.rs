@@ -1,140 +1,301 @@
 extern crate gl;
 extern crate glfw;
 
-use glfw::{Context, FlushedMessages};
+use std::{
+  borrow::Cow,
+  path::{Path, PathBuf},
+  time::Instant,
+};
+
+use glfw::Context;
 
 use crate::{
-  general::color::COLORS,
+  general::{bounds::Bounds, color::COLORS},
   gl_utils::{
-    gl_texture::Texture,
-    gl_translation::{DataType, DrawingMode, UsageMode},
-    shader_creator::{
-      Shader, ShaderProgram, VertexShaderAttribute, VertexShaderAttributeType,
-    },
-    vertex_array_object_handler::VertexArrayObject,
+    gl_texture::{Texture, TextureOptions},
+    gl_translation::{DrawingMode, UsageMode},
+    image_data::{ImageData, ImageError},
+    texture_atlas::{TextureAtlas, TextureAtlasBuilder},
   },
   init_debug_callback,
-  rendering::drawer::Drawer,
+  rendering::{
+    backend::{NullBackend, RenderBackend},
+    camera::Camera2D,
+    drawer::Drawer,
+    gl_backend::GlBackend,
+    scaling::ScalingPolicy,
+  },
   window::window::WindowConfig,
   Rectangle, Sprite,
 };
 
-use super::entity::StandardEntity;
+use super::{
+  component::ComponentContext,
+  entity::{Entity, StandardEntity},
+  entity_store::{EntityId, EntityStore},
+  message::{Message, MessageTarget},
+  query::{EntityQuery, EntityView},
+  time::Time,
+};
 
 /// Public interface for the game engine's capabilities
 /// Will be responsible for rendering, handling physics systems
 /// And controlling the game's state through entitiy data
 pub struct BowTie<'d> {
-  entities: Vec<StandardEntity<'d>>,
+  entities: EntityStore<'d>,
   drawer: Drawer<'d>,
-  shading_program: ShaderProgram,
-  glfw_instance: glfw::Glfw,
+  glfw_instance: Option<glfw::Glfw>,
   window: Option<glfw::Window>,
   events: Option<std::sync::mpsc::Receiver<(f64, glfw::WindowEvent)>>,
-}
-
-/// Initiates a shader program with pre-defined vertex attributes
-// TODO: This needs to be more flexible
-fn get_program() -> ShaderProgram {
-  let mut program = ShaderProgram::new();
-  program.load_shaders(vec![
-    Shader::VertexShader(
-      String::from("main"),
-      vec![
-        VertexShaderAttribute::new(
-          String::from("position"),
-          DataType::Float32,
-          2,
-          9 + (4 * 4),
-          true,
-          0,
-          VertexShaderAttributeType::Vector,
-        ),
-        VertexShaderAttribute::new(
-          String::from("targetColor"),
-          DataType::Float32,
-          4,
-          9 + (4 * 4),
-          true,
-          2,
-          VertexShaderAttributeType::Vector,
-        ),
-        VertexShaderAttribute::new(
-          String::from("tex_cords_in"),
-          DataType::Float32,
-          2,
-          9 + (4 * 4),
-          true,
-          6,
-          VertexShaderAttributeType::Vector,
-        ),
-        VertexShaderAttribute::new(
-          String::from("tex_id"),
-          DataType::Float32,
-          1,
-          9 + (4 * 4),
-          true,
-          8,
-          VertexShaderAttributeType::Vector,
-        ),
-        VertexShaderAttribute::new(
-          String::from("trans"),
-          DataType::Float32,
-          4,
-          9 + (4 * 4),
-          true,
-          9,
-          VertexShaderAttributeType::Matrix4,
-        ),
-      ],
-    ),
-    Shader::FragmentShader(String::from("main")),
-  ]);
-  program
+  should_close: bool,
+  messages: Vec<(MessageTarget, Message)>,
+  time: Time,
+  last_frame: Option<Instant>,
+  camera: Camera2D,
+  scaling_policy: ScalingPolicy,
+  framebuffer_size: Option<(u32, u32)>,
+  pending_events: Vec<glfw::WindowEvent>,
+  bounds: Option<Bounds>,
 }
 
 impl<'d> BowTie<'d> {
   pub fn new() -> BowTie<'d> {
-    let mut bowtie = BowTie {
-      entities: vec![],
-      drawer: Drawer::shell(),
-      shading_program: ShaderProgram::shell(),
-      glfw_instance: glfw::init(glfw::FAIL_ON_ERRORS).unwrap(),
+    BowTie {
+      entities: EntityStore::new(),
+      drawer: Drawer::new(Box::new(NullBackend::new())),
+      glfw_instance: Some(glfw::init(glfw::FAIL_ON_ERRORS).unwrap()),
       window: None,
       events: None,
-    };
-    bowtie.drawer.set_entities_array(&bowtie.entities);
-    bowtie
+      should_close: false,
+      messages: vec![],
+      time: Time::new(),
+      last_frame: None,
+      camera: Camera2D::default(),
+      scaling_policy: ScalingPolicy::default(),
+      framebuffer_size: None,
+      pending_events: vec![],
+      bounds: None,
+    }
+  }
+
+  /// Creates a BowTie that never touches glfw or OpenGL.
+  /// Ticks run every entity's components but skip rendering, which makes it
+  /// possible to simulate frames in tests or on machines without a display.
+  pub fn headless() -> BowTie<'d> {
+    BowTie::headless_with_backend(Box::new(NullBackend::new()))
+  }
+
+  /// Creates a headless BowTie that renders every tick through `backend`,
+  /// e.g. a `SoftwareBackend` to compare frames against golden images.
+  pub fn headless_with_backend(
+    backend: Box<dyn RenderBackend + 'd>,
+  ) -> BowTie<'d> {
+    let drawer = Drawer::new(backend);
+    let framebuffer_size = drawer.get_framebuffer_size();
+    BowTie {
+      entities: EntityStore::new(),
+      drawer,
+      glfw_instance: None,
+      window: None,
+      events: None,
+      should_close: false,
+      messages: vec![],
+      time: Time::new(),
+      last_frame: None,
+      camera: Camera2D::default(),
+      scaling_policy: ScalingPolicy::default(),
+      framebuffer_size,
+      pending_events: vec![],
+      bounds: None,
+    }
+  }
+
+  pub fn is_headless(&self) -> bool {
+    self.glfw_instance.is_none()
   }
 
   /// Loads the entity into the drawer and the game's state
   /// To handle rendering and physics
-  pub fn load_entity(
-    &mut self,
-    entity: StandardEntity<'d>,
-  ) -> &mut StandardEntity<'d> {
-    self.entities.push(entity);
-    let entity_id = self.entities.len() - 1;
-    &mut self.entities[entity_id]
+  ///
+  /// Returns the id to look the entity up with or unload it later
+  pub fn load_entity(&mut self, mut entity: StandardEntity<'d>) -> EntityId {
+    entity.set_scene_bounds(self.bounds);
+    let id = self.entities.insert(entity);
+    if let Some(entity) = self.entities.get(id) {
+      for component in entity.get_components() {
+        component.add_entity(id);
+      }
+    }
+    id
   }
 
-  pub fn unload_entity(&mut self, entity: StandardEntity<'d>) {}
+  /// Removes the entity from the game and from every one of its components'
+  /// stores. Returns the entity, or `None` if the id is stale.
+  pub fn unload_entity(&mut self, id: EntityId) -> Option<StandardEntity<'d>> {
+    let entity = self.entities.remove(id)?;
+    for component in entity.get_components() {
+      component.remove_entity(id);
+    }
+    Some(entity)
+  }
 
   pub fn get_entity_count(&self) -> usize {
     self.entities.len()
   }
 
-  /// Updates the entities with the existing systems
+  pub fn get_entity(&self, id: EntityId) -> Option<&StandardEntity<'d>> {
+    self.entities.get(id)
+  }
+
+  pub fn get_entity_mut(
+    &mut self,
+    id: EntityId,
+  ) -> Option<&mut StandardEntity<'d>> {
+    self.entities.get_mut(id)
+  }
+
+  pub fn get_entity_ids(&self) -> Vec<EntityId> {
+    self.entities.ids()
+  }
+
+  pub fn get_entities(&self) -> impl Iterator<Item = &StandardEntity<'d>> {
+    self.entities.iter()
+  }
+
+  /// Queries the loaded entities, by component, tag or region
+  pub fn query(&self) -> EntityQuery<'_> {
+    EntityQuery::new(Cow::Owned(self.get_entity_views()))
+  }
+
+  fn get_entity_views(&self) -> Vec<EntityView> {
+    self
+      .entities
+      .iter()
+      .filter_map(|entity| EntityView::from_entity(entity))
+      .collect()
+  }
+
+  pub fn get_bounds(&self) -> Option<Bounds> {
+    self.bounds
+  }
+
+  /// Bounds for every entity that doesn't have its own, see
+  /// `StandardEntity::set_bounds`. There are none by default.
+  pub fn set_bounds(&mut self, bounds: Option<Bounds>) {
+    self.bounds = bounds;
+    for entity in self.entities.iter_mut() {
+      entity.set_scene_bounds(bounds);
+    }
+  }
+
+  /// Number of ticks that have run since this BowTie was created
+  pub fn get_tick_count(&self) -> u64 {
+    self.time.get_frame_count()
+  }
+
+  pub fn get_time(&self) -> &Time {
+    &self.time
+  }
+
+  /// Sets how many fixed updates run per second of game time.
+  /// Defaults to `DEFAULT_UPDATE_RATE`.
+  pub fn set_fixed_update_rate(&mut self, updates_per_second: f32) {
+    self.time.set_update_rate(updates_per_second);
+  }
+
+  /// Speeds game time up or slows it down, e.g. 0.5 for slow motion.
+  /// Fixed updates keep the same delta, they just run more or less often.
+  pub fn set_time_scale(&mut self, time_scale: f32) {
+    self.time.set_time_scale(time_scale);
+  }
+
+  /// Stops running fixed updates. Frames are still drawn.
+  pub fn pause(&mut self) {
+    self.time.set_paused(true);
+  }
+
+  pub fn resume(&mut self) {
+    self.time.set_paused(false);
+  }
+
+  /// Runs one fixed update on the entities with the existing systems
+  ///
+  /// Every entity's components run first, then the messages they sent are
+  /// delivered, and finally despawned entities are unloaded.
   pub fn update_entities(&mut self) {
+    let context = ComponentContext::new(self.time, self.get_entity_views());
+    for entity in self.entities.iter_mut() {
+      entity.store_previous_position();
+      entity.act_on_components(&context);
+      self.messages.append(&mut entity.take_outbox());
+    }
+
+    self.dispatch_messages();
+
+    let despawned = self
+      .entities
+      .iter()
+      .filter(|entity| entity.is_despawned())
+      .filter_map(|entity| entity.get_id())
+      .collect::<Vec<EntityId>>();
+
+    for id in despawned {
+      self.unload_entity(id);
+    }
+
+    self.time.finish_update();
+  }
+
+  /// Queues a message from outside any entity.
+  /// It is delivered in the message phase of the next update.
+  pub fn send_message(&mut self, target: MessageTarget, message: Message) {
+    self.messages.push((target, message));
+  }
+
+  fn get_message_recipients(&self, target: &MessageTarget) -> Vec<EntityId> {
+    match target {
+      MessageTarget::Entity(id) => {
+        if self.entities.contains(*id) {
+          vec![*id]
+        } else {
+          vec![]
+        }
+      }
+      MessageTarget::Group(group) => self
+        .entities
+        .iter()
+        .filter(|entity| entity.has_tag(group))
+        .filter_map(|entity| entity.get_id())
+        .collect(),
+      MessageTarget::Broadcast => self.entities.ids(),
+    }
+  }
+
+  /// Delivers the queued messages to their recipients.
+  /// Messages sent while handling these are delivered on the next update.
+  fn dispatch_messages(&mut self) {
+    for entity in self.entities.iter_mut() {
+      entity.clear_inbox();
+    }
+
+    let messages = std::mem::take(&mut self.messages);
+    for (target, message) in messages {
+      for id in self.get_message_recipients(&target) {
+        if let Some(entity) = self.entities.get_mut(id) {
+          entity.recieve_message(message.to_owned());
+        }
+      }
+    }
+
     for entity in self.entities.iter_mut() {
-      entity.act_on_components();
+      self.messages.append(&mut entity.take_outbox());
     }
   }
 
   /// Prepares the god object to draw stuff.
   /// Has to be called before the main draw call
   pub fn prep_for_render(&mut self) {
-    if self.entities.len() < 1 {
+    if !self.is_headless() && self.entities.len() < 1 {
       self.load_entity(StandardEntity::new(
         Sprite::new(
           Rectangle::new(0.0, 0.0, 0.0, 0.0, COLORS::White.into()),
@@ -143,22 +304,146 @@ impl<'d> BowTie<'d> {
         0.0,
       ));
     }
-    self.drawer.prep_data(&self.shading_program);
-    self.shading_program.use_program();
-    self.drawer.prep_textures(&self.shading_program);
+    self.drawer.prep_textures(&self.entities);
+  }
+
+  pub fn get_camera(&self) -> &Camera2D {
+    &self.camera
+  }
+
+  /// Lets the camera be moved, zoomed or rotated in between ticks
+  pub fn get_camera_mut(&mut self) -> &mut Camera2D {
+    &mut self.camera
+  }
+
+  pub fn set_camera(&mut self, camera: Camera2D) {
+    self.camera = camera;
+  }
+
+  /// Directory textures read their `images/{name}.png` files from
+  pub fn get_asset_root(&self) -> &Path {
+    self.drawer.get_asset_root()
+  }
+
+  /// Sets the directory image files are read from, `.` (the working
+  /// directory) by default. Binaries that can be launched from anywhere can
+  /// use e.g. `env!("CARGO_MANIFEST_DIR")` or the executable's directory.
+  pub fn set_asset_root<P: Into<PathBuf>>(&mut self, asset_root: P) {
+    self.drawer.set_asset_root(asset_root.into());
+  }
+
+  pub fn is_hot_reloading(&self) -> bool {
+    self.drawer.is_hot_reloading()
+  }
+
+  /// Development mode that checks asset files for changes every
+  /// `HOT_RELOAD_INTERVAL`, re-uploading changed textures and recompiling
+  /// changed shaders in place. Off by default.
+  ///
+  /// Images that fail to reload are reported by `take_reload_errors`.
+  pub fn set_hot_reload(&mut self, hot_reload: bool) {
+    self.drawer.set_hot_reload(hot_reload);
+  }
+
+  /// Reloads changed asset files right away, without waiting for the next
+  /// check. Works whether hot reloading is on or not.
+  pub fn reload_changed_assets(&mut self) {
+    self.drawer.reload_changed_assets();
+  }
+
+  /// Image names and errors of the textures hot reloading couldn't read
+  /// since the last call, see `Drawer::take_reload_errors`
+  pub fn take_reload_errors(&mut self) -> Vec<(String, ImageError)> {
+    self.drawer.take_reload_errors()
+  }
+
+  /// Names and errors of the materials that didn't compile since the last
+  /// call, see `Drawer::take_material_errors`. They are drawn with the
+  /// built-in shaders instead.
+  pub fn take_material_errors(&mut self) -> Vec<(String, String)> {
+    self.drawer.take_material_errors()
+  }
+
+  /// Reads and decodes `images/{image_name}.png` under the asset root right
+  /// away, instead of on the first draw, so a missing or broken file is
+  /// reported to the caller
+  pub fn load_texture(
+    &self,
+    image_name: &str,
+    options: TextureOptions,
+  ) -> Result<Texture, ImageError> {
+    let image = Texture::new(image_name, options)
+      .load_image_data(self.get_asset_root())?;
+    Ok(Texture::from_image_data(image_name, image, options))
+  }
+
+  /// Packs the textures of every loaded entity into atlas pages of at most
+  /// `page_size` pixels squared and points their sprites at the pages.
+  /// Entities loaded later can use the atlas with `TextureAtlas::apply`.
+  pub fn pack_textures(
+    &mut self,
+    page_size: u32,
+  ) -> Result<TextureAtlas, ImageError> {
+    let mut builder = TextureAtlasBuilder::new(page_size);
+    builder.set_asset_root(self.get_asset_root().to_path_buf());
+    for entity in self.entities.iter() {
+      builder.add_texture(&entity.get_sprite().texture)?;
+    }
+
+    let atlas = builder.build();
+    for entity in self.entities.iter_mut() {
+      atlas.apply(entity.get_sprite_mut());
+    }
+    Ok(atlas)
+  }
+
+  pub fn get_scaling_policy(&self) -> ScalingPolicy {
+    self.scaling_policy
+  }
+
+  /// Sets how the camera's view is fit into the window when their aspect
+  /// ratios differ. Defaults to `ScalingPolicy::Stretch`.
+  pub fn set_scaling_policy(&mut self, scaling_policy: ScalingPolicy) {
+    self.scaling_policy = scaling_policy;
+  }
+
+  /// Size of the window's framebuffer in pixels, kept up to date when the
+  /// window is resized
+  pub fn get_framebuffer_size(&self) -> Option<(u32, u32)> {
+    self.framebuffer_size
   }
 
   /// Draws the entities with an actual clear screen refresh
   pub fn draw_entities(&mut self) {
     self.drawer.clear_screen(COLORS::White.into());
-    self
-      .drawer
-      .draw(DrawingMode::Triangles, &self.shading_program);
+
+    let mut camera = self.camera;
+    if let Some((width, height)) = self.framebuffer_size {
+      let (fitted_camera, viewport) =
+        self.scaling_policy.apply(&self.camera, width, height);
+      camera = fitted_camera;
+      self.drawer.set_viewport(viewport);
+    }
+    self.drawer.set_camera(&camera);
+    self.drawer.draw(
+      DrawingMode::Triangles,
+      &self.entities,
+      self.time.get_alpha(),
+    );
+  }
+
+  /// Returns the last drawn frame if the render backend can read it back
+  pub fn read_pixels(&self) -> Option<ImageData> {
+    self.drawer.read_pixels()
   }
 
   pub fn create_window(&mut self, window_config: WindowConfig) {
-    let (mut window, events) = self
+    let glfw_instance = self
       .glfw_instance
+      .as_mut()
+      .expect("Cannot create a window on a headless BowTie");
+
+    let (mut window, events) = glfw_instance
       .create_window(
         window_config.width.into(),
         window_config.height.into(),
@@ -169,63 +454,115 @@ impl<'d> BowTie<'d> {
 
     window.make_current();
 
-    gl::load_with(|s| self.glfw_instance.get_proc_address_raw(s));
+    gl::load_with(|s| glfw_instance.get_proc_address_raw(s));
 
     // OpenGL 3.2
-    self
-      .glfw_instance
-      .window_hint(glfw::WindowHint::ContextVersionMajor(3));
-    self
-      .glfw_instance
-      .window_hint(glfw::WindowHint::ContextVersionMinor(2));
-    self
-      .glfw_instance
-      .window_hint(glfw::WindowHint::OpenGlProfile(
-        glfw::OpenGlProfileHint::Core,
-      ));
-    self
-      .glfw_instance
-      .window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
+    glfw_instance.window_hint(glfw::WindowHint::ContextVersionMajor(3));
+    glfw_instance.window_hint(glfw::WindowHint::ContextVersionMinor(2));
+    glfw_instance.window_hint(glfw::WindowHint::OpenGlProfile(
+      glfw::OpenGlProfileHint::Core,
+    ));
+    glfw_instance.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
 
-    self
-      .glfw_instance
-      .set_swap_interval(glfw::SwapInterval::Sync(1));
+    glfw_instance.set_swap_interval(glfw::SwapInterval::Sync(1));
 
     init_debug_callback();
 
     window.make_current();
     window.set_key_polling(true);
     window.set_sticky_keys(true);
+    window.set_framebuffer_size_polling(true);
+
+    let asset_root = self.get_asset_root().to_path_buf();
+    let hot_reload = self.drawer.is_hot_reloading();
+    self.drawer = Drawer::new(Box::new(GlBackend::new(UsageMode::StaticDraw)));
+    self.drawer.set_asset_root(asset_root);
+    self.drawer.set_hot_reload(hot_reload);
 
-    self.shading_program = get_program();
-    self.drawer = Drawer::new(UsageMode::StaticDraw);
-    self.drawer.set_entities_array(&self.entities);
+    let (width, height) = window.get_framebuffer_size();
+    self.framebuffer_size = Some((width as u32, height as u32));
 
     self.window = Option::Some(window);
     self.events = Option::Some(events);
   }
 
-  pub fn flush_events(&self) -> Vec<glfw::WindowEvent> {
-    let events = glfw::flush_messages(self.events.as_ref().unwrap());
-    let mut window_events: Vec<glfw::WindowEvent> = vec![];
-    for (_, event) in events {
-      window_events.push(event);
+  /// Moves the window's events over to `pending_events`, handling the
+  /// ones BowTie cares about itself on the way
+  fn collect_events(&mut self) {
+    let events = match self.events.as_ref() {
+      Some(events) => events,
+      None => return,
+    };
+    for (_, event) in glfw::flush_messages(events) {
+      if let glfw::WindowEvent::FramebufferSize(width, height) = event {
+        self.framebuffer_size =
+          Some((width.max(0) as u32, height.max(0) as u32));
+      }
+      self.pending_events.push(event);
     }
-    window_events
   }
 
-  pub fn tick(&mut self) {
-    self.window.as_mut().unwrap().swap_buffers();
-    self.glfw_instance.poll_events();
-    self.update_entities();
+  /// Returns the window events since the last call.
+  /// Framebuffer resizes have already been applied to the viewport.
+  pub fn flush_events(&mut self) -> Vec<glfw::WindowEvent> {
+    self.collect_events();
+    std::mem::take(&mut self.pending_events)
+  }
+
+  /// Presents the last frame, runs the fixed updates `frame_time` seconds
+  /// call for and draws the next frame
+  fn run_frame(&mut self, frame_time: f32) {
+    if let Some(window) = self.window.as_mut() {
+      window.swap_buffers();
+    }
+    if let Some(glfw_instance) = self.glfw_instance.as_mut() {
+      glfw_instance.poll_events();
+    }
+    self.collect_events();
+
+    let updates = self.time.accumulate(frame_time);
+    for _ in 0..updates {
+      self.update_entities();
+    }
+
+    self.time.finish_frame();
     self.draw_entities();
   }
 
+  /// Runs a frame with as many fixed updates as the time since the last
+  /// tick calls for
+  pub fn tick(&mut self) {
+    let now = Instant::now();
+    let frame_time = match self.last_frame {
+      Some(last_frame) => now.duration_since(last_frame).as_secs_f32(),
+      None => self.time.get_delta(),
+    };
+    self.last_frame = Some(now);
+    self.run_frame(frame_time);
+  }
+
+  /// Runs `ticks` ticks back to back, each one as if exactly one fixed
+  /// update's worth of time had passed. Unlike `tick` it doesn't depend on
+  /// the clock, so the results are deterministic.
+  /// Mostly useful on a headless BowTie to simulate frames programmatically.
+  pub fn step(&mut self, ticks: u32) {
+    for _ in 0..ticks {
+      self.run_frame(self.time.get_delta());
+    }
+    self.last_frame = Some(Instant::now());
+  }
+
   pub fn should_close(&self) -> bool {
-    self.window.as_ref().unwrap().should_close()
+    match self.window.as_ref() {
+      Some(window) => window.should_close(),
+      None => self.should_close,
+    }
   }
 
   pub fn set_should_close(&mut self, should: bool) {
-    self.window.as_mut().unwrap().set_should_close(should)
+    match self.window.as_mut() {
+      Some(window) => window.set_should_close(should),
+      None => self.should_close = should,
+    }
   }
 }
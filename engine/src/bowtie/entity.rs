@@ -1,11 +1,18 @@
+use super::{
+  component::{ComponentContext, StandardComponent},
+  entity_store::EntityId,
+  message::{Message, MessageTarget},
+};
 use crate::{
-  general::direction, gl_utils::gl_texture::LoadableTexture,
-  rendering::drawer::DrawableData, sprites::drawable::Drawable, Direction,
-  Rectangle, Sprite,
+  general::{bounds::Bounds, bounds::BoundsBehavior, direction},
+  gl_utils::gl_texture::LoadableTexture,
+  rendering::{
+    drawer::{DrawableData, VERTEX_POSITION_OFFSET, VERTEX_SIZE},
+    layer::RenderLayer,
+  },
+  sprites::drawable::Drawable,
+  Direction, Shape, Sprite,
 };
-use std::collections::HashMap;
-
-use super::component::StandardComponent;
 
 /// Entity trait
 /// This is the basis for any object that can be acted upon within the engine
@@ -14,6 +21,9 @@ use super::component::StandardComponent;
 /// Should also have a function that recieves messages so the components
 /// have an effect.
 pub trait Entity<'a> {
+  /// Id handed out when the entity was loaded, `None` before that
+  fn get_id(&self) -> Option<EntityId>;
+
   fn get_drawable(&self) -> DrawableData;
 
   fn get_x(&self) -> f32;
@@ -34,25 +44,150 @@ pub trait Entity<'a> {
 
 #[derive(Clone)]
 pub struct StandardEntity<'s> {
-  sprite: Sprite<'s, Rectangle>,
+  sprite: Sprite<'s, Box<dyn Shape>>,
   speed: f32,
   components: Vec<StandardComponent<'s>>,
   direction: Direction,
   collision_direction: Direction,
+  id: Option<EntityId>,
+  despawned: bool,
+  tags: Vec<String>,
+  inbox: Vec<Message>,
+  outbox: Vec<(MessageTarget, Message)>,
+  previous_position: Option<(f32, f32)>,
+  scene_bounds: Option<Bounds>,
+  z_index: i32,
+  /// Increases with every entity loaded, slots are reused so the id's
+  /// index doesn't tell the load order
+  load_sequence: u64,
 }
 
 impl<'s> StandardEntity<'s> {
-  pub fn new(sprite: Sprite<'s, Rectangle>, speed: f32) -> StandardEntity<'s> {
+  /// Takes a sprite of any shape, e.g. a `Rectangle`
+  pub fn new<TShape: Shape + 'static>(
+    sprite: Sprite<'s, TShape>,
+    speed: f32,
+  ) -> StandardEntity<'s> {
     StandardEntity {
-      sprite,
+      sprite: sprite.boxed(),
       speed,
       components: vec![],
       direction: Direction::Stationary,
       collision_direction: Direction::Stationary,
+      id: None,
+      despawned: false,
+      tags: vec![],
+      inbox: vec![],
+      outbox: vec![],
+      previous_position: None,
+      scene_bounds: None,
+      z_index: RenderLayer::World.into(),
+      load_sequence: 0,
+    }
+  }
+
+  pub fn get_sprite(&self) -> &Sprite<'s, Box<dyn Shape>> {
+    &self.sprite
+  }
+
+  pub fn get_sprite_mut(&mut self) -> &mut Sprite<'s, Box<dyn Shape>> {
+    &mut self.sprite
+  }
+
+  pub(crate) fn set_id(&mut self, id: EntityId) {
+    self.id = Some(id);
+  }
+
+  pub(crate) fn set_load_sequence(&mut self, load_sequence: u64) {
+    self.load_sequence = load_sequence;
+  }
+
+  /// Where the entity was loaded among every entity loaded so far, used to
+  /// draw entities with the same z-index in load order
+  pub(crate) fn get_load_sequence(&self) -> u64 {
+    self.load_sequence
+  }
+
+  /// Key components should use for this entity's state in their store
+  pub fn get_store_key(&self) -> String {
+    match self.id {
+      Some(id) => id.to_string(),
+      None => String::from("unloaded"),
+    }
+  }
+
+  /// Entities with a higher z-index are drawn on top. Entities with the same
+  /// z-index are drawn in the order they were loaded.
+  pub fn set_z_index(&mut self, z_index: i32) {
+    self.z_index = z_index;
+  }
+
+  pub fn get_z_index(&self) -> i32 {
+    self.z_index
+  }
+
+  /// Moves the entity to one of the named layers, see `RenderLayer`
+  pub fn set_layer(&mut self, layer: RenderLayer) {
+    self.z_index = layer.into();
+  }
+
+  /// Marks the entity to be unloaded once the current update is done.
+  /// Lets components kill the entity they are acting on, e.g. a bullet.
+  pub fn despawn(&mut self) {
+    self.despawned = true;
+  }
+
+  pub fn is_despawned(&self) -> bool {
+    self.despawned
+  }
+
+  /// Tags double as message groups, see `MessageTarget::Group`
+  pub fn add_tag(&mut self, tag: &str) {
+    if !self.has_tag(tag) {
+      self.tags.push(String::from(tag));
     }
   }
 
-  pub fn move_in_direction(&mut self, direction: Direction, strength: f32) {
+  pub fn remove_tag(&mut self, tag: &str) {
+    self.tags.retain(|existing| existing != tag);
+  }
+
+  pub fn has_tag(&self, tag: &str) -> bool {
+    self.tags.iter().any(|existing| existing == tag)
+  }
+
+  pub fn get_tags(&self) -> &Vec<String> {
+    &self.tags
+  }
+
+  /// Queues a message. It is delivered in the message phase of the current
+  /// tick, after every entity's components have run.
+  pub fn send_message(&mut self, target: MessageTarget, mut message: Message) {
+    message.set_sender(self.id);
+    self.outbox.push((target, message));
+  }
+
+  /// Messages delivered to this entity in the last message phase
+  pub fn get_messages(&self) -> &Vec<Message> {
+    &self.inbox
+  }
+
+  pub(crate) fn take_outbox(&mut self) -> Vec<(MessageTarget, Message)> {
+    std::mem::take(&mut self.outbox)
+  }
+
+  pub(crate) fn clear_inbox(&mut self) {
+    self.inbox.clear();
+  }
+
+  /// Moves the entity, unless it's blocked by a collision, and keeps it in
+  /// its bounds. Returns the edges of the bounds it went past,
+  /// `Direction::Stationary` if none.
+  pub fn move_in_direction(
+    &mut self,
+    direction: Direction,
+    strength: f32,
+  ) -> Direction {
     let (x, y) =
       (direction.subtract_direction(self.collision_direction)).as_vector();
 
@@ -61,6 +196,45 @@ impl<'s> StandardEntity<'s> {
 
     self.set_x(self.get_x() + x_strength);
     self.set_y(self.get_y() + y_strength);
+
+    self.confine_to_bounds()
+  }
+
+  /// Bounds just for this entity, overriding the scene's bounds set with
+  /// `BowTie::set_bounds`
+  pub fn set_bounds(&mut self, bounds: Option<Bounds>) {
+    self.sprite.set_bounds(bounds);
+  }
+
+  /// The entity's own bounds, falling back to the scene's
+  pub fn get_bounds(&self) -> Option<Bounds> {
+    self.sprite.get_bounds().or(self.scene_bounds)
+  }
+
+  pub(crate) fn set_scene_bounds(&mut self, bounds: Option<Bounds>) {
+    self.scene_bounds = bounds;
+  }
+
+  /// Applies the entity's bounds to its current position, despawning it if
+  /// it left bounds set to `BoundsBehavior::Despawn`.
+  /// Entities that wrap around are drawn on their new side right away,
+  /// rather than interpolated across the bounds.
+  /// Returns the edges it went past.
+  pub fn confine_to_bounds(&mut self) -> Direction {
+    let bounds = match self.get_bounds() {
+      Some(bounds) => bounds,
+      None => return Direction::Stationary,
+    };
+
+    let hit = self.sprite.confine_to(&bounds);
+    if hit != Direction::Stationary {
+      match bounds.behavior {
+        BoundsBehavior::Despawn => self.despawn(),
+        BoundsBehavior::Wrap => self.store_previous_position(),
+        BoundsBehavior::Clamp => {}
+      }
+    }
+    hit
   }
 
   pub fn set_collision_direction(&mut self, direction: Direction) {
@@ -74,26 +248,53 @@ impl<'s> StandardEntity<'s> {
       .find(|component| component.get_name() == name)
   }
 
-  // TODO: Figure out a way to make this safe
-  pub fn act_on_components(&mut self) {
-    let mut components: Vec<*const StandardComponent> = vec![];
-    let entity_ref: *mut StandardEntity<'s> = self;
-    unsafe {
-      let len = self.components.len();
-      for i in 0..len {
-        let comp_ptr: *const StandardComponent = &self.components[i];
-        components.push(comp_ptr);
-      }
+  /// Runs every component on this entity, in the order they were loaded.
+  ///
+  /// Components are cheap handles around shared functions and stores, so the
+  /// list is cloned before running them. That way each component can borrow
+  /// the entity mutably while the entity keeps its own component list, so
+  /// other components' stores stay readable through `get_component`.
+  pub fn act_on_components(&mut self, context: &ComponentContext) {
+    let components = self.components.to_owned();
+    for component in components.iter() {
+      component.act(self, context);
+    }
+  }
 
-      for component in components {
-        let comp = component.as_ref().unwrap();
-        comp.act(entity_ref.as_mut().unwrap());
-      }
+  /// Remembers where the entity was before the upcoming fixed update,
+  /// so frames drawn in between updates can be interpolated
+  pub(crate) fn store_previous_position(&mut self) {
+    self.previous_position = Some((self.get_x(), self.get_y()));
+  }
+
+  /// Drawable data with the position blended between the previous fixed
+  /// update and the current one. `alpha` of 1.0 draws the current position.
+  pub fn get_interpolated_drawable(&self, alpha: f32) -> DrawableData {
+    let mut drawable = self.get_drawable();
+    let (previous_x, previous_y) = match self.previous_position {
+      Some(position) => position,
+      None => return drawable,
+    };
+
+    let offset_x = (previous_x - self.get_x()) * (1.0 - alpha);
+    let offset_y = (previous_y - self.get_y()) * (1.0 - alpha);
+    if offset_x == 0.0 && offset_y == 0.0 {
+      return drawable;
+    }
+
+    for vertex in drawable.vertices.chunks_mut(VERTEX_SIZE) {
+      vertex[VERTEX_POSITION_OFFSET] += offset_x;
+      vertex[VERTEX_POSITION_OFFSET + 1] += offset_y;
     }
+    drawable
   }
 }
 
 impl<'a> Entity<'a> for StandardEntity<'a> {
+  fn get_id(&self) -> Option<EntityId> {
+    self.id
+  }
+
   fn get_x(&self) -> f32 {
     self.sprite.get_x()
   }
@@ -125,6 +326,7 @@ impl<'a> Entity<'a> for StandardEntity<'a> {
       vertices: self.sprite.get_vertices(),
       elements: self.sprite.get_elements(),
       texture: self.sprite.texture.to_owned(),
+      material: self.sprite.get_material().cloned(),
       corner_count: self.sprite.get_corner_count(),
     }
   }
@@ -137,7 +339,15 @@ impl<'a> Entity<'a> for StandardEntity<'a> {
     self.components.push(component);
   }
 
-  fn recieve_message(&mut self, message: Message) {}
+  /// Keeps the message for `get_messages` and hands it to every component
+  /// subscribed to its type
+  fn recieve_message(&mut self, message: Message) {
+    let components = self.components.to_owned();
+    for component in components.iter() {
+      component.handle_message(self, &message);
+    }
+    self.inbox.push(message);
+  }
 }
 
 /// Component Trait
@@ -145,40 +355,17 @@ impl<'a> Entity<'a> for StandardEntity<'a> {
 /// the engine
 ///
 /// E.g: A collision component
+///
+/// Load it onto entities with `StandardComponent::from_component`.
 pub trait Component<'a> {
   fn get_name(&self) -> &str;
 
-  /// Act function recieves information about current entities and returns a
+  /// Act function recieves the entity it is acting on and returns a
   /// message with a type and a HashMap of values. This is to say, entities can
   /// interact with this information differently.
   ///
   /// E.g: An entity may choose to ignore a report of collision.
-  unsafe fn act(
-    &mut self,
-    entities: &Vec<*mut dyn Entity<'a>>,
-    entity: *mut dyn Entity<'a>,
-  ) -> Option<Message>;
-}
-
-/// Messages that will be communicated between components and entities
-pub struct Message {
-  message_type: String,
-  values: HashMap<String, f32>,
-}
-
-impl Message {
-  pub fn new(message_type: String, values: HashMap<String, f32>) -> Message {
-    Message {
-      message_type,
-      values,
-    }
-  }
-
-  pub fn get_message_type(&self) -> String {
-    self.message_type.to_owned()
-  }
-
-  pub fn get_values(&self) -> HashMap<String, f32> {
-    self.values.to_owned()
-  }
+  ///
+  /// The message is sent to the entity itself, like `send_message` would.
+  fn act(&mut self, entity: &mut dyn Entity<'a>) -> Option<Message>;
 }
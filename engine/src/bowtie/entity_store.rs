@@ -0,0 +1,144 @@
+use std::fmt::Display;
+
+use super::entity::StandardEntity;
+
+/// Stable handle to an entity loaded into `BowTie`.
+///
+/// The index is reused once an entity is unloaded, so every handle also
+/// carries the generation of the slot it was created for. Handles to unloaded
+/// entities never resolve to whatever got loaded in their place.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct EntityId {
+  index: u32,
+  generation: u32,
+}
+
+impl EntityId {
+  pub fn get_index(&self) -> u32 {
+    self.index
+  }
+
+  pub fn get_generation(&self) -> u32 {
+    self.generation
+  }
+}
+
+impl Display for EntityId {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}v{}", self.index, self.generation)
+  }
+}
+
+struct EntitySlot<'s> {
+  generation: u32,
+  entity: Option<StandardEntity<'s>>,
+}
+
+/// Generational storage for the loaded entities
+pub struct EntityStore<'s> {
+  slots: Vec<EntitySlot<'s>>,
+  free_indices: Vec<u32>,
+  len: usize,
+  /// Load sequence the next inserted entity gets
+  next_load_sequence: u64,
+}
+
+impl<'s> EntityStore<'s> {
+  pub fn new() -> EntityStore<'s> {
+    EntityStore {
+      slots: vec![],
+      free_indices: vec![],
+      len: 0,
+      next_load_sequence: 0,
+    }
+  }
+
+  pub fn insert(&mut self, mut entity: StandardEntity<'s>) -> EntityId {
+    let index = match self.free_indices.pop() {
+      Some(index) => index,
+      None => {
+        self.slots.push(EntitySlot {
+          generation: 0,
+          entity: None,
+        });
+        (self.slots.len() - 1) as u32
+      }
+    };
+
+    let slot = &mut self.slots[index as usize];
+    let id = EntityId {
+      index,
+      generation: slot.generation,
+    };
+
+    entity.set_id(id);
+    entity.set_load_sequence(self.next_load_sequence);
+    self.next_load_sequence += 1;
+    slot.entity = Some(entity);
+    self.len += 1;
+
+    id
+  }
+
+  pub fn remove(&mut self, id: EntityId) -> Option<StandardEntity<'s>> {
+    let slot = self.slots.get_mut(id.index as usize)?;
+    if slot.generation != id.generation {
+      return None;
+    }
+
+    let entity = slot.entity.take()?;
+    slot.generation += 1;
+    self.free_indices.push(id.index);
+    self.len -= 1;
+
+    Some(entity)
+  }
+
+  pub fn get(&self, id: EntityId) -> Option<&StandardEntity<'s>> {
+    let slot = self.slots.get(id.index as usize)?;
+    if slot.generation != id.generation {
+      return None;
+    }
+    slot.entity.as_ref()
+  }
+
+  pub fn get_mut(&mut self, id: EntityId) -> Option<&mut StandardEntity<'s>> {
+    let slot = self.slots.get_mut(id.index as usize)?;
+    if slot.generation != id.generation {
+      return None;
+    }
+    slot.entity.as_mut()
+  }
+
+  pub fn contains(&self, id: EntityId) -> bool {
+    self.get(id).is_some()
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn ids(&self) -> Vec<EntityId> {
+    self
+      .slots
+      .iter()
+      .enumerate()
+      .filter(|(_, slot)| slot.entity.is_some())
+      .map(|(index, slot)| EntityId {
+        index: index as u32,
+        generation: slot.generation,
+      })
+      .collect()
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = &StandardEntity<'s>> {
+    self.slots.iter().filter_map(|slot| slot.entity.as_ref())
+  }
+
+  pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut StandardEntity<'s>> {
+    self
+      .slots
+      .iter_mut()
+      .filter_map(|slot| slot.entity.as_mut())
+  }
+}
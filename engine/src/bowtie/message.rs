@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+use crate::general::value::Value;
+
+use super::entity_store::EntityId;
+
+/// Who a message should be delivered to
+#[derive(Debug, Clone, PartialEq)]
+pub enum MessageTarget {
+  Entity(EntityId),
+  /// Every entity tagged with the group's name
+  Group(String),
+  /// Every loaded entity, including the sender
+  Broadcast,
+}
+
+/// Messages that will be communicated between components and entities
+#[derive(Debug, Clone)]
+pub struct Message {
+  message_type: String,
+  values: HashMap<String, Value>,
+  sender: Option<EntityId>,
+}
+
+impl Message {
+  pub fn new(message_type: String, values: HashMap<String, Value>) -> Message {
+    Message {
+      message_type,
+      values,
+      sender: None,
+    }
+  }
+
+  pub fn get_message_type(&self) -> String {
+    self.message_type.to_owned()
+  }
+
+  pub fn get_values(&self) -> HashMap<String, Value> {
+    self.values.to_owned()
+  }
+
+  pub fn get_value(&self, key: &str) -> Option<&Value> {
+    self.values.get(key)
+  }
+
+  /// Entity that sent the message, `None` if it came from outside an entity
+  pub fn get_sender(&self) -> Option<EntityId> {
+    self.sender
+  }
+
+  pub(crate) fn set_sender(&mut self, sender: Option<EntityId>) {
+    self.sender = sender;
+  }
+}
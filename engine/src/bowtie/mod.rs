@@ -1,3 +1,9 @@
 pub mod component;
 pub mod controller;
 pub mod entity;
+pub mod entity_store;
+pub mod message;
+pub mod query;
+pub mod time;
+
+mod test;
@@ -0,0 +1,188 @@
+use std::borrow::Cow;
+
+use super::{
+  entity::{Entity, StandardEntity},
+  entity_store::EntityId,
+};
+
+/// Read only snapshot of an entity, handed out by queries
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityView {
+  id: EntityId,
+  x: f32,
+  y: f32,
+  width: f32,
+  height: f32,
+  tags: Vec<String>,
+  components: Vec<String>,
+}
+
+impl EntityView {
+  /// `None` if the entity hasn't been loaded yet
+  pub fn from_entity(entity: &StandardEntity) -> Option<EntityView> {
+    Some(EntityView {
+      id: entity.get_id()?,
+      x: entity.get_x(),
+      y: entity.get_y(),
+      width: entity.get_width(),
+      height: entity.get_height(),
+      tags: entity.get_tags().to_owned(),
+      components: entity
+        .get_components()
+        .iter()
+        .map(|component| component.get_name())
+        .collect(),
+    })
+  }
+
+  pub fn get_id(&self) -> EntityId {
+    self.id
+  }
+
+  pub fn get_x(&self) -> f32 {
+    self.x
+  }
+
+  pub fn get_y(&self) -> f32 {
+    self.y
+  }
+
+  pub fn get_width(&self) -> f32 {
+    self.width
+  }
+
+  pub fn get_height(&self) -> f32 {
+    self.height
+  }
+
+  pub fn get_tags(&self) -> &Vec<String> {
+    &self.tags
+  }
+
+  pub fn has_tag(&self, tag: &str) -> bool {
+    self.tags.iter().any(|existing| existing == tag)
+  }
+
+  pub fn has_component(&self, name: &str) -> bool {
+    self.components.iter().any(|existing| existing == name)
+  }
+
+  /// Whether the entity overlaps the region.
+  /// Like entities, regions hang down and to the right from `(x, y)`.
+  pub fn overlaps(&self, x: f32, y: f32, width: f32, height: f32) -> bool {
+    self.x < x + width
+      && self.x + self.width > x
+      && self.y - self.height < y
+      && self.y > y - height
+  }
+}
+
+enum QueryFilter {
+  Component(String),
+  Tag(String),
+  Region(f32, f32, f32, f32),
+  Excluding(EntityId),
+}
+
+impl QueryFilter {
+  fn matches(&self, view: &EntityView) -> bool {
+    match self {
+      QueryFilter::Component(name) => view.has_component(name),
+      QueryFilter::Tag(tag) => view.has_tag(tag),
+      QueryFilter::Region(x, y, width, height) => {
+        view.overlaps(*x, *y, *width, *height)
+      }
+      QueryFilter::Excluding(id) => view.get_id() != *id,
+    }
+  }
+}
+
+/// Query builder over the loaded entities
+///
+/// Every filter narrows the results down further, e.g:
+///
+/// ```ignore
+/// let ball = context.query().with_tag("ball").first();
+/// ```
+pub struct EntityQuery<'q> {
+  entities: Cow<'q, [EntityView]>,
+  filters: Vec<QueryFilter>,
+}
+
+impl<'q> EntityQuery<'q> {
+  pub fn new(entities: Cow<'q, [EntityView]>) -> EntityQuery<'q> {
+    EntityQuery {
+      entities,
+      filters: vec![],
+    }
+  }
+
+  /// Only entities with a component of that name loaded
+  pub fn with_component(mut self, name: &str) -> EntityQuery<'q> {
+    self
+      .filters
+      .push(QueryFilter::Component(String::from(name)));
+    self
+  }
+
+  pub fn with_tag(mut self, tag: &str) -> EntityQuery<'q> {
+    self.filters.push(QueryFilter::Tag(String::from(tag)));
+    self
+  }
+
+  /// Only entities that overlap the region
+  pub fn in_region(
+    mut self,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+  ) -> EntityQuery<'q> {
+    self.filters.push(QueryFilter::Region(x, y, width, height));
+    self
+  }
+
+  /// Leaves an entity out, usually the one doing the querying
+  pub fn excluding(mut self, id: EntityId) -> EntityQuery<'q> {
+    self.filters.push(QueryFilter::Excluding(id));
+    self
+  }
+
+  fn matches(&self, view: &EntityView) -> bool {
+    self.filters.iter().all(|filter| filter.matches(view))
+  }
+
+  pub fn collect(&self) -> Vec<EntityView> {
+    self
+      .entities
+      .iter()
+      .filter(|view| self.matches(view))
+      .cloned()
+      .collect()
+  }
+
+  pub fn first(&self) -> Option<EntityView> {
+    self
+      .entities
+      .iter()
+      .find(|view| self.matches(view))
+      .cloned()
+  }
+
+  pub fn ids(&self) -> Vec<EntityId> {
+    self
+      .entities
+      .iter()
+      .filter(|view| self.matches(view))
+      .map(|view| view.get_id())
+      .collect()
+  }
+
+  pub fn count(&self) -> usize {
+    self
+      .entities
+      .iter()
+      .filter(|view| self.matches(view))
+      .count()
+  }
+}
@@ -0,0 +1,789 @@
+#[cfg(test)]
+mod controller_tests {
+  use std::{collections::HashMap, sync::Arc};
+
+  use crate::{
+    premade_components::{
+      Animation, AnimationComponent, AnimationMode, AnimationState,
+      CollisionComponent, EventComponent, FallingObject, GravityComponent,
+      ANIMATION_FINISHED, PLAY_ANIMATION,
+    },
+    Bounds, BoundsBehavior, BowTie, Direction, Entity, Message, MessageTarget,
+    Rectangle, Sprite, SpriteSheet, StandardComponent, StandardEntity, Texture,
+    TextureRegion, Value, COLORS,
+  };
+
+  fn rectangle_entity<'a>(x: f32, y: f32) -> StandardEntity<'a> {
+    StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(x, y, 0.1, 0.1, COLORS::White.into()),
+        Texture::none(),
+      ),
+      0.0,
+    )
+  }
+
+  fn move_right_component<'a>() -> StandardComponent<'a> {
+    StandardComponent::new(
+      Arc::new(|entity, _store, _context| {
+        entity.move_in_direction(Direction::Right, 0.01);
+      }),
+      "move_right",
+      HashMap::new(),
+    )
+  }
+
+  #[test]
+  fn headless_step_runs_components() {
+    let mut bowtie = BowTie::headless();
+    let mut entity = rectangle_entity(0.0, 0.0);
+    entity.load_components(move_right_component());
+    let id = bowtie.load_entity(entity);
+
+    bowtie.step(10);
+
+    let entity = bowtie.get_entity(id).unwrap();
+    assert!((entity.get_x() - 0.1).abs() < 0.0001);
+    assert_eq!(entity.get_y(), 0.0);
+    assert_eq!(bowtie.get_tick_count(), 10);
+  }
+
+  #[test]
+  fn headless_should_close() {
+    let mut bowtie = BowTie::headless();
+    assert!(bowtie.is_headless());
+    assert!(!bowtie.should_close());
+    assert!(bowtie.flush_events().is_empty());
+
+    bowtie.set_should_close(true);
+    assert!(bowtie.should_close());
+  }
+
+  #[test]
+  fn unload_entity_invalidates_id() {
+    let mut bowtie = BowTie::headless();
+    let first = bowtie.load_entity(rectangle_entity(0.0, 0.0));
+    let second = bowtie.load_entity(rectangle_entity(0.5, 0.0));
+
+    assert!(bowtie.unload_entity(first).is_some());
+    assert!(bowtie.unload_entity(first).is_none());
+    assert!(bowtie.get_entity(first).is_none());
+    assert_eq!(bowtie.get_entity_count(), 1);
+
+    // The freed slot is reused without resurrecting the old id
+    let third = bowtie.load_entity(rectangle_entity(-0.5, 0.0));
+    assert_eq!(third.get_index(), first.get_index());
+    assert_ne!(third, first);
+    assert!(bowtie.get_entity(first).is_none());
+    assert_eq!(bowtie.get_entity(third).unwrap().get_x(), -0.5);
+    assert_eq!(bowtie.get_entity(second).unwrap().get_x(), 0.5);
+    assert_eq!(bowtie.get_entity_ids(), vec![third, second]);
+  }
+
+  #[test]
+  fn unload_entity_clears_component_stores() {
+    let mut gravity = GravityComponent::new(0.002);
+    let gravity_comp = gravity.component();
+
+    let mut bowtie = BowTie::headless();
+    let mut entity = rectangle_entity(0.0, 0.5);
+    entity.load_components(gravity_comp.to_owned());
+    let id = bowtie.load_entity(entity);
+
+    bowtie.step(2);
+
+    let falling_count = || {
+      gravity_comp
+        .get_typed_store::<Option<FallingObject>>()
+        .unwrap()
+        .lock()
+        .unwrap()
+        .len()
+    };
+
+    assert_eq!(falling_count(), 1);
+    bowtie.unload_entity(id);
+    assert_eq!(falling_count(), 0);
+  }
+
+  #[test]
+  fn components_can_despawn_entities() {
+    let despawn_right = StandardComponent::new(
+      Arc::new(|entity, _store, _context| {
+        if entity.get_x() > 0.05 {
+          entity.despawn();
+        }
+      }),
+      "despawn_right",
+      HashMap::new(),
+    );
+
+    let mut bowtie = BowTie::headless();
+    let mut entity = rectangle_entity(0.0, 0.0);
+    entity.load_components(move_right_component());
+    entity.load_components(despawn_right);
+    let id = bowtie.load_entity(entity);
+
+    bowtie.step(5);
+    assert!(bowtie.get_entity(id).is_some());
+
+    bowtie.step(1);
+    assert!(bowtie.get_entity(id).is_none());
+    assert_eq!(bowtie.get_entity_count(), 0);
+  }
+
+  #[test]
+  fn collision_is_keyed_by_entity_id() {
+    let collision = CollisionComponent::new();
+
+    let mut bowtie = BowTie::headless();
+    let mut left = rectangle_entity(0.0, 0.0);
+    left.load_components(collision.component());
+    let mut right = rectangle_entity(0.05, 0.0);
+    right.load_components(collision.component());
+    let mut far = rectangle_entity(0.8, 0.8);
+    far.load_components(collision.component());
+
+    let left_id = bowtie.load_entity(left);
+    let right_id = bowtie.load_entity(right);
+    let far_id = bowtie.load_entity(far);
+
+    bowtie.step(2);
+
+    assert!(collision.get_is_collided(left_id));
+    assert!(collision.get_is_collided(right_id));
+    assert!(!collision.get_is_collided(far_id));
+
+    bowtie.unload_entity(right_id);
+    bowtie.step(1);
+    assert!(!collision.get_is_collided(left_id));
+  }
+
+  #[test]
+  fn collisions_register_on_both_entities_in_the_first_update() {
+    let collision = CollisionComponent::new();
+
+    let mut bowtie = BowTie::headless();
+    let mut first = rectangle_entity(0.0, 0.0);
+    first.load_components(collision.component());
+    let mut second = rectangle_entity(0.05, 0.0);
+    second.load_components(collision.component());
+
+    let first_id = bowtie.load_entity(first);
+    let second_id = bowtie.load_entity(second);
+
+    bowtie.step(1);
+    assert!(collision.get_is_collided(first_id));
+    assert!(collision.get_is_collided(second_id));
+  }
+
+  #[test]
+  fn collision_components_are_separate_groups() {
+    let players = CollisionComponent::new();
+    let walls = CollisionComponent::new();
+
+    let mut bowtie = BowTie::headless();
+    let mut player = rectangle_entity(0.0, 0.0);
+    player.load_components(players.component());
+    let mut other_player = rectangle_entity(0.8, 0.8);
+    other_player.load_components(players.component());
+    let mut wall = rectangle_entity(0.05, 0.0);
+    wall.load_components(walls.component());
+
+    let player_id = bowtie.load_entity(player);
+    bowtie.load_entity(other_player);
+    let wall_id = bowtie.load_entity(wall);
+
+    bowtie.step(1);
+    assert!(!players.get_is_collided(player_id));
+    assert!(!walls.get_is_collided(wall_id));
+  }
+
+  #[test]
+  fn components_read_other_components_stores() {
+    let counter = StandardComponent::new(
+      Arc::new(|entity, store, _context| {
+        let mut locked_store = store.lock().unwrap();
+        let count = locked_store
+          .entry(entity.get_store_key())
+          .or_insert(Value::Number(0.0));
+        if let Value::Number(count) = count {
+          *count += 1.0;
+        }
+      }),
+      "counter",
+      HashMap::new(),
+    );
+
+    let follow_counter = StandardComponent::new(
+      Arc::new(|entity, _store, _context| {
+        let count = match entity.get_component("counter") {
+          Some(counter) => {
+            match counter
+              .get_store()
+              .lock()
+              .unwrap()
+              .get(&entity.get_store_key())
+            {
+              Some(Value::Number(count)) => count.to_owned(),
+              _ => 0.0,
+            }
+          }
+          None => 0.0,
+        };
+        entity.set_x(count / 10.0);
+      }),
+      "follow_counter",
+      HashMap::new(),
+    );
+
+    let mut bowtie = BowTie::headless();
+    let mut entity = rectangle_entity(0.0, 0.0);
+    entity.load_components(counter);
+    entity.load_components(follow_counter);
+    let id = bowtie.load_entity(entity);
+
+    bowtie.step(3);
+
+    assert!((bowtie.get_entity(id).unwrap().get_x() - 0.3).abs() < 0.0001);
+  }
+
+  #[test]
+  fn components_can_load_components() {
+    let spawner = StandardComponent::new(
+      Arc::new(|entity, _store, _context| {
+        if entity.get_component("move_right").is_none() {
+          entity.load_components(move_right_component());
+        }
+      }),
+      "spawner",
+      HashMap::new(),
+    );
+
+    let mut bowtie = BowTie::headless();
+    let mut entity = rectangle_entity(0.0, 0.0);
+    entity.load_components(spawner);
+    let id = bowtie.load_entity(entity);
+
+    // The new component only runs from the next update on
+    bowtie.step(3);
+
+    let entity = bowtie.get_entity(id).unwrap();
+    assert_eq!(entity.get_components().len(), 2);
+    assert!((entity.get_x() - 0.02).abs() < 0.0001);
+  }
+
+  fn ping() -> Message {
+    Message::new(
+      String::from("ping"),
+      HashMap::from([(String::from("strength"), Value::Number(0.1))]),
+    )
+  }
+
+  #[test]
+  fn messages_reach_targeted_entities() {
+    let mut bowtie = BowTie::headless();
+    let first = bowtie.load_entity(rectangle_entity(0.0, 0.0));
+    let second = bowtie.load_entity(rectangle_entity(0.0, 0.0));
+
+    bowtie.send_message(MessageTarget::Entity(second), ping());
+    bowtie.step(1);
+
+    assert!(bowtie.get_entity(first).unwrap().get_messages().is_empty());
+    let messages = bowtie.get_entity(second).unwrap().get_messages();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].get_message_type(), "ping");
+    assert_eq!(messages[0].get_sender(), None);
+
+    // Inboxes only hold the last message phase
+    bowtie.step(1);
+    assert!(bowtie.get_entity(second).unwrap().get_messages().is_empty());
+  }
+
+  #[test]
+  fn messages_reach_groups_and_broadcasts() {
+    let mut bowtie = BowTie::headless();
+    let mut tagged = rectangle_entity(0.0, 0.0);
+    tagged.add_tag("enemies");
+    let tagged = bowtie.load_entity(tagged);
+    let untagged = bowtie.load_entity(rectangle_entity(0.0, 0.0));
+
+    bowtie.send_message(MessageTarget::Group(String::from("enemies")), ping());
+    bowtie.step(1);
+    assert_eq!(bowtie.get_entity(tagged).unwrap().get_messages().len(), 1);
+    assert!(bowtie
+      .get_entity(untagged)
+      .unwrap()
+      .get_messages()
+      .is_empty());
+
+    bowtie.send_message(MessageTarget::Broadcast, ping());
+    bowtie.step(1);
+    assert_eq!(bowtie.get_entity(tagged).unwrap().get_messages().len(), 1);
+    assert_eq!(bowtie.get_entity(untagged).unwrap().get_messages().len(), 1);
+  }
+
+  #[test]
+  fn event_components_deliver_pushed_messages() {
+    let mut events = EventComponent::new();
+
+    let mut bowtie = BowTie::headless();
+    let mut entity = rectangle_entity(0.0, 0.0);
+    entity.load_components(events.component());
+    let id = bowtie.load_entity(entity);
+
+    events.push_message(id, ping());
+    events.push_message(id, Message::new(String::from("pong"), HashMap::new()));
+
+    let message_types = |bowtie: &BowTie| {
+      bowtie
+        .get_entity(id)
+        .unwrap()
+        .get_messages()
+        .iter()
+        .map(|message| message.get_message_type())
+        .collect::<Vec<String>>()
+    };
+
+    bowtie.step(1);
+    assert_eq!(message_types(&bowtie), vec!["ping"]);
+    bowtie.step(1);
+    assert_eq!(message_types(&bowtie), vec!["pong"]);
+    bowtie.step(1);
+    assert!(message_types(&bowtie).is_empty());
+  }
+
+  #[test]
+  fn subscribed_components_handle_messages() {
+    let mut pusher = StandardComponent::new(
+      Arc::new(|_entity, _store, _context| {}),
+      "pusher",
+      HashMap::new(),
+    );
+    pusher.on_message(
+      "ping",
+      Arc::new(|entity, _store, message| {
+        if let Some(Value::Number(strength)) = message.get_value("strength") {
+          entity.move_in_direction(Direction::Right, *strength);
+        }
+      }),
+    );
+
+    let mut bowtie = BowTie::headless();
+    let sender_id = bowtie.load_entity(rectangle_entity(0.0, 0.0));
+    let mut receiver = rectangle_entity(0.0, 0.0);
+    receiver.load_components(pusher);
+    let receiver_id = bowtie.load_entity(receiver);
+
+    // Components send messages to other entities while they update
+    let sender = bowtie.get_entity_mut(sender_id).unwrap();
+    sender.load_components(StandardComponent::new(
+      Arc::new(move |entity, _store, _context| {
+        entity.send_message(MessageTarget::Entity(receiver_id), ping());
+      }),
+      "sender",
+      HashMap::new(),
+    ));
+
+    bowtie.step(2);
+
+    let receiver = bowtie.get_entity(receiver_id).unwrap();
+    assert!((receiver.get_x() - 0.2).abs() < 0.0001);
+    assert_eq!(receiver.get_messages()[0].get_sender(), Some(sender_id));
+  }
+
+  #[test]
+  fn time_scale_and_pause_change_update_count() {
+    let mut bowtie = BowTie::headless();
+    let mut entity = rectangle_entity(0.0, 0.0);
+    entity.load_components(move_right_component());
+    let id = bowtie.load_entity(entity);
+
+    bowtie.set_time_scale(0.5);
+    bowtie.step(4);
+    assert_eq!(bowtie.get_time().get_update_count(), 2);
+    assert!((bowtie.get_entity(id).unwrap().get_x() - 0.02).abs() < 0.0001);
+
+    bowtie.set_time_scale(1.0);
+    bowtie.pause();
+    bowtie.step(3);
+    assert!(bowtie.get_time().is_paused());
+    assert_eq!(bowtie.get_time().get_update_count(), 2);
+    assert_eq!(bowtie.get_tick_count(), 7);
+
+    bowtie.resume();
+    bowtie.step(1);
+    assert!((bowtie.get_entity(id).unwrap().get_x() - 0.03).abs() < 0.0001);
+  }
+
+  #[test]
+  fn components_read_time() {
+    let per_second = StandardComponent::new(
+      Arc::new(|entity, _store, context| {
+        let time = context.get_time();
+        entity.move_in_direction(Direction::Right, 0.5 * time.get_delta());
+        entity.set_y(time.get_elapsed() as f32);
+      }),
+      "per_second",
+      HashMap::new(),
+    );
+
+    let mut bowtie = BowTie::headless();
+    bowtie.set_fixed_update_rate(20.0);
+    let mut entity = rectangle_entity(0.0, 0.0);
+    entity.load_components(per_second);
+    let id = bowtie.load_entity(entity);
+
+    bowtie.step(20);
+
+    // Components see the elapsed time from before their update
+    let entity = bowtie.get_entity(id).unwrap();
+    assert!((entity.get_x() - 0.5).abs() < 0.0001);
+    assert!((entity.get_y() - 0.95).abs() < 0.0001);
+    assert!((bowtie.get_time().get_elapsed() - 1.0).abs() < 0.0001);
+  }
+
+  #[derive(Debug, Default, Clone, PartialEq)]
+  struct Odometer {
+    distance: f32,
+    updates: u32,
+  }
+
+  #[test]
+  fn typed_components_keep_state_per_entity() {
+    let odometer = StandardComponent::typed::<Odometer>(
+      Arc::new(|entity, state, _context| {
+        state.updates += 1;
+        state.distance += entity.get_x();
+      }),
+      "odometer",
+    );
+
+    let mut bowtie = BowTie::headless();
+    let mut near = rectangle_entity(0.1, 0.0);
+    near.load_components(odometer.to_owned());
+    let mut far = rectangle_entity(0.5, 0.0);
+    far.load_components(odometer.to_owned());
+    let near = bowtie.load_entity(near);
+    let far = bowtie.load_entity(far);
+
+    bowtie.step(2);
+
+    let far_state = odometer.get_state::<Odometer>(far).unwrap();
+    assert_eq!(far_state.updates, 2);
+    assert!((far_state.distance - 1.0).abs() < 0.0001);
+    assert!(
+      (odometer.get_state::<Odometer>(near).unwrap().distance - 0.2).abs()
+        < 0.0001
+    );
+
+    // Asking for the wrong state type finds nothing
+    assert!(odometer.get_state::<f32>(near).is_none());
+
+    bowtie.unload_entity(near);
+    assert!(odometer.get_state::<Odometer>(near).is_none());
+    assert_eq!(
+      odometer
+        .get_typed_store::<Odometer>()
+        .unwrap()
+        .lock()
+        .unwrap()
+        .len(),
+      1
+    );
+  }
+
+  #[test]
+  fn queries_filter_by_component_tag_and_region() {
+    let mut bowtie = BowTie::headless();
+    let mut ball = rectangle_entity(0.5, 0.5);
+    ball.add_tag("ball");
+    let ball = bowtie.load_entity(ball);
+    let mut mover = rectangle_entity(-0.5, 0.5);
+    mover.load_components(move_right_component());
+    let mover = bowtie.load_entity(mover);
+    let still = bowtie.load_entity(rectangle_entity(-0.5, -0.5));
+
+    assert_eq!(bowtie.query().count(), 3);
+    assert_eq!(bowtie.query().with_tag("ball").ids(), vec![ball]);
+    assert_eq!(
+      bowtie.query().with_component("move_right").ids(),
+      vec![mover]
+    );
+    assert_eq!(
+      bowtie.query().in_region(-1.0, 0.0, 1.0, 1.0).ids(),
+      vec![still]
+    );
+    assert_eq!(
+      bowtie
+        .query()
+        .in_region(-1.0, 1.0, 2.0, 1.0)
+        .excluding(ball)
+        .ids(),
+      vec![mover]
+    );
+    assert!(bowtie
+      .query()
+      .with_tag("ball")
+      .in_region(-1.0, 1.0, 1.0, 1.0)
+      .first()
+      .is_none());
+  }
+
+  #[test]
+  fn components_query_other_entities() {
+    let follow_ball = StandardComponent::new(
+      Arc::new(|entity, _store, context| {
+        if let Some(ball) = context.query().with_tag("ball").first() {
+          entity.set_y(ball.get_y());
+        }
+      }),
+      "follow_ball",
+      HashMap::new(),
+    );
+
+    let mut bowtie = BowTie::headless();
+    let mut follower = rectangle_entity(-0.5, 0.0);
+    follower.load_components(follow_ball);
+    let follower = bowtie.load_entity(follower);
+
+    let mut ball = rectangle_entity(0.5, 0.3);
+    ball.add_tag("ball");
+    ball.load_components(StandardComponent::new(
+      Arc::new(|entity, _store, _context| {
+        entity.move_in_direction(Direction::Up, 0.1);
+      }),
+      "move_up",
+      HashMap::new(),
+    ));
+    bowtie.load_entity(ball);
+
+    bowtie.step(1);
+    assert!((bowtie.get_entity(follower).unwrap().get_y() - 0.3).abs() < 0.0001);
+
+    // Queries see where the ball was when the update started
+    bowtie.step(1);
+    assert!((bowtie.get_entity(follower).unwrap().get_y() - 0.4).abs() < 0.0001);
+  }
+
+  #[test]
+  fn moves_are_unbounded_by_default() {
+    let mut entity = rectangle_entity(0.9, 0.9);
+
+    let hit = entity.move_in_direction(Direction::UpRight, 0.5);
+
+    assert_eq!(hit, Direction::Stationary);
+    assert!((entity.get_x() - 1.4).abs() < 0.0001);
+    assert!((entity.get_y() - 1.4).abs() < 0.0001);
+  }
+
+  #[test]
+  fn scene_bounds_clamp_entities() {
+    let mut bowtie = BowTie::headless();
+    bowtie.set_bounds(Some(Bounds::screen(BoundsBehavior::Clamp)));
+    let mut entity = rectangle_entity(0.85, 0.0);
+    entity.load_components(move_right_component());
+    let id = bowtie.load_entity(entity);
+
+    bowtie.step(10);
+
+    let entity = bowtie.get_entity_mut(id).unwrap();
+    assert!((entity.get_x() - 0.9).abs() < 0.0001);
+    assert_eq!(
+      entity.move_in_direction(Direction::UpRight, 1.5),
+      Direction::UpRight
+    );
+    assert!((entity.get_y() - 1.0).abs() < 0.0001);
+  }
+
+  #[test]
+  fn entity_bounds_override_scene_bounds() {
+    let mut bowtie = BowTie::headless();
+    let mut entity = rectangle_entity(0.9, 0.0);
+    entity.set_bounds(Some(Bounds::screen(BoundsBehavior::Wrap)));
+    let id = bowtie.load_entity(entity);
+    bowtie.set_bounds(Some(Bounds::screen(BoundsBehavior::Clamp)));
+
+    let entity = bowtie.get_entity_mut(id).unwrap();
+    assert_eq!(
+      entity.move_in_direction(Direction::Right, 0.05),
+      Direction::Stationary
+    );
+
+    // Only wraps around once it's fully out, coming in from the other side
+    assert_eq!(
+      entity.move_in_direction(Direction::Right, 0.1),
+      Direction::Right
+    );
+    assert!((entity.get_x() - -1.05).abs() < 0.0001);
+  }
+
+  #[test]
+  fn despawn_bounds_unload_leaving_entities() {
+    let mut bowtie = BowTie::headless();
+    bowtie.set_bounds(Some(Bounds::screen(BoundsBehavior::Despawn)));
+    let mut entity = rectangle_entity(0.955, 0.0);
+    entity.load_components(move_right_component());
+    let id = bowtie.load_entity(entity);
+
+    bowtie.step(4);
+    assert!(bowtie.get_entity(id).is_some());
+
+    bowtie.step(1);
+    assert!(bowtie.get_entity(id).is_none());
+  }
+
+  #[test]
+  fn sprite_moves_report_bounds_hit() {
+    let mut sprite = Sprite::new(
+      Rectangle::new(0.0, 0.0, 0.5, 0.5, COLORS::White.into()),
+      Texture::none(),
+    );
+    assert_eq!(sprite.move_up(2.0), Direction::Stationary);
+
+    sprite.set_bounds(Some(Bounds::screen(BoundsBehavior::Clamp)));
+    assert_eq!(sprite.move_up(0.5), Direction::Up);
+    assert_eq!(sprite.get_y(), 1.0);
+    assert_eq!(sprite.move_left(3.0), Direction::Left);
+    assert_eq!(sprite.get_x(), -1.0);
+    assert_eq!(sprite.move_down(0.5), Direction::Stationary);
+  }
+
+  #[test]
+  fn animations_flip_through_sprite_sheet_frames() {
+    let sheet = SpriteSheet::grid(Texture::none(), 4, 1);
+    let mut animation = AnimationComponent::new(sheet.to_owned());
+    animation.add_animation(
+      "walk",
+      Animation::new(vec![0, 1, 2], 10.0, AnimationMode::Loop),
+    );
+    animation.add_animation(
+      "die",
+      Animation::new(vec![3, 2], 10.0, AnimationMode::Once),
+    );
+
+    let mut bowtie = BowTie::headless();
+    bowtie.set_fixed_update_rate(10.0);
+    let mut entity = rectangle_entity(0.0, 0.0);
+    entity.load_components(animation.component());
+    let id = bowtie.load_entity(entity);
+
+    let region_of = |bowtie: &BowTie, id| {
+      bowtie
+        .get_entity(id)
+        .unwrap()
+        .get_sprite()
+        .get_texture_region()
+    };
+    let finished_animations = |bowtie: &BowTie, id| {
+      bowtie
+        .get_entity(id)
+        .unwrap()
+        .get_messages()
+        .iter()
+        .filter(|message| message.get_message_type() == ANIMATION_FINISHED)
+        .filter_map(|message| match message.get_value("animation") {
+          Some(Value::String(name)) => Some(name.to_owned()),
+          _ => None,
+        })
+        .collect::<Vec<String>>()
+    };
+
+    // The first animation plays by default, one frame per update
+    bowtie.step(1);
+    assert_eq!(region_of(&bowtie, id), sheet.get_frame(0).unwrap());
+    bowtie.step(2);
+    assert_eq!(region_of(&bowtie, id), sheet.get_frame(2).unwrap());
+    assert!(finished_animations(&bowtie, id).is_empty());
+
+    bowtie.step(1);
+    assert_eq!(region_of(&bowtie, id), sheet.get_frame(0).unwrap());
+    assert_eq!(finished_animations(&bowtie, id), vec!["walk"]);
+
+    let mut play_die = HashMap::new();
+    play_die.insert(String::from("animation"), Value::String("die".into()));
+    bowtie.send_message(
+      MessageTarget::Entity(id),
+      Message::new(String::from(PLAY_ANIMATION), play_die),
+    );
+    bowtie.step(2);
+    assert_eq!(region_of(&bowtie, id), sheet.get_frame(3).unwrap());
+
+    bowtie.step(2);
+    assert_eq!(region_of(&bowtie, id), sheet.get_frame(2).unwrap());
+    assert_eq!(finished_animations(&bowtie, id), vec!["die"]);
+    assert!(animation.get_state(id).unwrap().finished);
+
+    // Once animations stay on their last frame
+    bowtie.step(3);
+    assert_eq!(region_of(&bowtie, id), sheet.get_frame(2).unwrap());
+    assert!(finished_animations(&bowtie, id).is_empty());
+
+    // Playing a finished animation again starts it over
+    animation.play(id, "die");
+    bowtie.step(1);
+    assert_eq!(region_of(&bowtie, id), sheet.get_frame(3).unwrap());
+    assert!(!animation.get_state(id).unwrap().finished);
+
+    animation.play(id, "walk");
+    bowtie.step(1);
+    assert_eq!(region_of(&bowtie, id), sheet.get_frame(0).unwrap());
+  }
+
+  #[test]
+  fn ping_pong_animations_bounce_between_ends() {
+    let animation = Animation::new(vec![5, 6, 7], 1.0, AnimationMode::PingPong);
+    let mut state = AnimationState::default();
+    state.play("bounce");
+
+    let mut frames = vec![];
+    let mut reached_end = vec![];
+    for _ in 0..7 {
+      reached_end.push(state.advance(&animation, 1.0));
+      frames.push(animation.frames[state.frame]);
+    }
+
+    assert_eq!(frames, vec![5, 6, 7, 6, 5, 6, 7]);
+    assert_eq!(
+      reached_end,
+      vec![false, false, false, false, true, false, false]
+    );
+  }
+
+  #[test]
+  fn sprite_sheets_slice_textures() {
+    let mut sheet = SpriteSheet::grid(Texture::none(), 2, 2);
+    assert_eq!(sheet.get_frame_count(), 4);
+    assert_eq!(
+      sheet.get_frame(1).unwrap(),
+      TextureRegion::new(0.5, 0.0, 0.5, 0.5)
+    );
+    assert_eq!(
+      sheet.get_frame(2).unwrap(),
+      TextureRegion::new(0.0, 0.5, 0.5, 0.5)
+    );
+
+    let jump = sheet
+      .add_named_frame("jump", TextureRegion::from_pixels(8, 0, 8, 16, 32, 16));
+    assert_eq!(jump, 4);
+    assert_eq!(
+      sheet.get_named_frame("jump").unwrap(),
+      TextureRegion::new(0.25, 0.0, 0.25, 1.0)
+    );
+    assert!(sheet.get_named_frame("fall").is_none());
+
+    let sprite = Sprite::from_sheet(
+      Rectangle::new(0.0, 0.0, 1.0, 1.0, COLORS::White.into()),
+      &sheet,
+      3,
+    );
+    assert_eq!(
+      sprite.get_texture_region().map_corner([1.0, 1.0]),
+      [1.0, 1.0]
+    );
+    assert_eq!(
+      sprite.get_texture_region().map_corner([0.0, 0.0]),
+      [0.5, 0.5]
+    );
+  }
+}
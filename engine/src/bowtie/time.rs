@@ -0,0 +1,127 @@
+/// Fixed updates per second unless `BowTie::set_fixed_update_rate` says
+/// otherwise
+pub const DEFAULT_UPDATE_RATE: f32 = 60.0;
+
+/// Longest frame the accumulator takes in, in seconds.
+/// Stops a long hitch (e.g. dragging the window) from queueing up so many
+/// fixed updates that the game can never catch up again.
+pub const MAX_FRAME_TIME: f32 = 0.25;
+
+/// Time resource
+///
+/// Entities are updated at a fixed rate that doesn't depend on how often
+/// frames are drawn. Every frame adds the (scaled) time it took to an
+/// accumulator, and one fixed update runs for every `delta` seconds in it.
+/// Whatever is left over becomes `alpha`, which the drawer uses to blend
+/// between the last two updates.
+#[derive(Debug, Copy, Clone)]
+pub struct Time {
+  delta: f32,
+  frame_delta: f32,
+  elapsed: f64,
+  frame_count: u64,
+  update_count: u64,
+  alpha: f32,
+  time_scale: f32,
+  paused: bool,
+  accumulator: f64,
+}
+
+impl Time {
+  pub fn new() -> Time {
+    Time {
+      delta: 1.0 / DEFAULT_UPDATE_RATE,
+      frame_delta: 0.0,
+      elapsed: 0.0,
+      frame_count: 0,
+      update_count: 0,
+      alpha: 0.0,
+      time_scale: 1.0,
+      paused: false,
+      accumulator: 0.0,
+    }
+  }
+
+  /// Seconds of game time covered by a single fixed update
+  pub fn get_delta(&self) -> f32 {
+    self.delta
+  }
+
+  /// Real seconds the last frame took, clamped to `MAX_FRAME_TIME`
+  pub fn get_frame_delta(&self) -> f32 {
+    self.frame_delta
+  }
+
+  /// Seconds of game time simulated so far
+  pub fn get_elapsed(&self) -> f64 {
+    self.elapsed
+  }
+
+  /// Number of frames drawn so far
+  pub fn get_frame_count(&self) -> u64 {
+    self.frame_count
+  }
+
+  /// Number of fixed updates run so far
+  pub fn get_update_count(&self) -> u64 {
+    self.update_count
+  }
+
+  /// How far the current frame is between the last update and the next one,
+  /// from 0.0 to 1.0
+  pub fn get_alpha(&self) -> f32 {
+    self.alpha
+  }
+
+  pub fn get_time_scale(&self) -> f32 {
+    self.time_scale
+  }
+
+  pub fn is_paused(&self) -> bool {
+    self.paused
+  }
+
+  pub(crate) fn set_update_rate(&mut self, updates_per_second: f32) {
+    assert!(
+      updates_per_second > 0.0,
+      "The fixed update rate has to be positive"
+    );
+    self.delta = 1.0 / updates_per_second;
+    self.accumulator = 0.0;
+    self.alpha = 0.0;
+  }
+
+  pub(crate) fn set_time_scale(&mut self, time_scale: f32) {
+    self.time_scale = time_scale.max(0.0);
+  }
+
+  pub(crate) fn set_paused(&mut self, paused: bool) {
+    self.paused = paused;
+  }
+
+  /// Adds a frame's worth of time to the accumulator.
+  /// Returns how many fixed updates have to run to catch up with it.
+  pub(crate) fn accumulate(&mut self, frame_time: f32) -> u32 {
+    self.frame_delta = frame_time.clamp(0.0, MAX_FRAME_TIME);
+    if !self.paused {
+      self.accumulator += (self.frame_delta * self.time_scale) as f64;
+    }
+
+    let mut updates = 0;
+    while self.accumulator >= self.delta as f64 {
+      self.accumulator -= self.delta as f64;
+      updates += 1;
+    }
+    updates
+  }
+
+  pub(crate) fn finish_update(&mut self) {
+    self.elapsed += self.delta as f64;
+    self.update_count += 1;
+  }
+
+  pub(crate) fn finish_frame(&mut self) {
+    self.alpha = (self.accumulator / self.delta as f64) as f32;
+    self.frame_count += 1;
+  }
+}
@@ -0,0 +1,241 @@
+use std::{
+  collections::HashMap,
+  marker::PhantomData,
+  sync::{Arc, Mutex},
+};
+
+use crate::{
+  bowtie::{component::TypedStore, entity::Entity},
+  sprites::sprite_sheet::SpriteSheet,
+  EntityId, Message, MessageTarget, StandardComponent, Value,
+};
+
+/// Message type an entity receives whenever one of its animations reaches
+/// its end, with the animation's name under `"animation"`
+pub const ANIMATION_FINISHED: &str = "animation_finished";
+
+/// Message type that switches the receiving entity's animation to the one
+/// named under `"animation"`
+pub const PLAY_ANIMATION: &str = "play_animation";
+
+/// What an animation does once it shows its last frame
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AnimationMode {
+  /// Starts over from the first frame
+  Loop,
+  /// Plays backwards to the first frame, then forwards again
+  PingPong,
+  /// Stops on the last frame
+  Once,
+}
+
+/// Frames of a sprite sheet shown one after the other
+#[derive(Debug, Clone)]
+pub struct Animation {
+  pub frames: Vec<usize>,
+  pub fps: f32,
+  pub mode: AnimationMode,
+}
+
+impl Animation {
+  pub fn new(frames: Vec<usize>, fps: f32, mode: AnimationMode) -> Animation {
+    Animation { frames, fps, mode }
+  }
+}
+
+/// Where an entity is in its current animation
+#[derive(Debug, Clone, Default)]
+pub struct AnimationState {
+  /// `None` until the entity's first update, which starts the default
+  /// animation
+  pub animation: Option<String>,
+  /// Position in the animation's frames, not a sprite sheet index
+  pub frame: usize,
+  pub elapsed: f32,
+  pub reversed: bool,
+  pub finished: bool,
+}
+
+impl AnimationState {
+  /// Switches to another animation, starting it from its first frame.
+  /// Does nothing if the animation is already playing, finished `Once`
+  /// animations start over.
+  pub fn play(&mut self, name: &str) {
+    if self.animation.as_deref() == Some(name) && !self.finished {
+      return;
+    }
+
+    *self = AnimationState {
+      animation: Some(String::from(name)),
+      ..AnimationState::default()
+    };
+  }
+
+  /// Shows the current frame for another `delta` seconds, first moving on
+  /// if it has been shown for long enough.
+  /// Returns whether the animation reached its end on the way.
+  pub fn advance(&mut self, animation: &Animation, delta: f32) -> bool {
+    let frame_count = animation.frames.len();
+    if self.finished || frame_count == 0 || animation.fps <= 0.0 {
+      return false;
+    }
+
+    let frame_time = 1.0 / animation.fps;
+    let mut reached_end = false;
+
+    while self.elapsed >= frame_time {
+      self.elapsed -= frame_time;
+
+      match animation.mode {
+        AnimationMode::Loop => {
+          self.frame += 1;
+          if self.frame >= frame_count {
+            self.frame = 0;
+            reached_end = true;
+          }
+        }
+        AnimationMode::PingPong => {
+          if frame_count == 1 {
+            reached_end = true;
+          } else if self.reversed {
+            self.frame -= 1;
+            if self.frame == 0 {
+              self.reversed = false;
+              reached_end = true;
+            }
+          } else {
+            self.frame += 1;
+            if self.frame == frame_count - 1 {
+              self.reversed = true;
+            }
+          }
+        }
+        AnimationMode::Once => {
+          if self.frame + 1 >= frame_count {
+            self.finished = true;
+            return true;
+          }
+          self.frame += 1;
+        }
+      }
+    }
+
+    self.elapsed += delta;
+    reached_end
+  }
+}
+
+/// Animation Component
+///
+/// Flips through the frames of a sprite sheet, changing the region of the
+/// texture the entity's sprite shows. Frames stay relative to the sheet's
+/// image when the sprite's texture is packed into a `TextureAtlas`.
+///
+/// The first animation added plays by default. Others are started with
+/// `play` or by sending the entity a `PLAY_ANIMATION` message. Every time
+/// an animation reaches its end the entity gets an `ANIMATION_FINISHED`
+/// message, after every loop for looping animations.
+pub struct AnimationComponent<'d> {
+  sheet: SpriteSheet,
+  animations: HashMap<String, Animation>,
+  default_animation: Option<String>,
+  states: TypedStore<AnimationState>,
+  _marker: PhantomData<&'d i32>,
+}
+
+impl<'d> AnimationComponent<'d> {
+  pub fn new(sheet: SpriteSheet) -> AnimationComponent<'d> {
+    AnimationComponent {
+      sheet,
+      animations: HashMap::new(),
+      default_animation: None,
+      states: Arc::new(Mutex::new(HashMap::new())),
+      _marker: PhantomData,
+    }
+  }
+
+  pub fn get_name() -> String {
+    String::from("animation")
+  }
+
+  /// Add animations before creating the component with `component`,
+  /// components created earlier don't know about later animations
+  pub fn add_animation(&mut self, name: &str, animation: Animation) {
+    if self.default_animation.is_none() {
+      self.default_animation = Some(String::from(name));
+    }
+    self.animations.insert(String::from(name), animation);
+  }
+
+  /// Switches the entity's animation, see `AnimationState::play`
+  pub fn play(&self, id: EntityId, name: &str) {
+    let mut states = self.states.lock().unwrap();
+    states.entry(id).or_default().play(name);
+  }
+
+  pub fn get_state(&self, id: EntityId) -> Option<AnimationState> {
+    self.states.lock().unwrap().get(&id).cloned()
+  }
+
+  pub fn component(&self) -> StandardComponent<'d> {
+    let sheet = self.sheet.to_owned();
+    let animations = self.animations.to_owned();
+    let default_animation = self.default_animation.to_owned();
+
+    let mut component = StandardComponent::with_typed_store(
+      Arc::new(move |entity, state: &mut AnimationState, context| {
+        if state.animation.is_none() {
+          match &default_animation {
+            Some(name) => state.play(name),
+            None => return,
+          }
+        }
+
+        let name = state.animation.to_owned().unwrap_or_default();
+        let animation = match animations.get(&name) {
+          Some(animation) => animation,
+          None => return,
+        };
+
+        let reached_end =
+          state.advance(animation, context.get_time().get_delta());
+
+        if let Some(region) = animation
+          .frames
+          .get(state.frame)
+          .and_then(|index| sheet.get_frame(*index))
+        {
+          entity.get_sprite_mut().set_image_region(region);
+        }
+
+        if let (true, Some(id)) = (reached_end, entity.get_id()) {
+          entity.send_message(
+            MessageTarget::Entity(id),
+            Message::new(
+              String::from(ANIMATION_FINISHED),
+              HashMap::from([(String::from("animation"), Value::String(name))]),
+            ),
+          );
+        }
+      }),
+      AnimationComponent::get_name().as_str(),
+      self.states.clone(),
+    );
+
+    let states = self.states.clone();
+    component.on_message(
+      PLAY_ANIMATION,
+      Arc::new(move |entity, _store, message| {
+        let id = match entity.get_id() {
+          Some(id) => id,
+          None => return,
+        };
+        if let Some(Value::String(name)) = message.get_value("animation") {
+          states.lock().unwrap().entry(id).or_default().play(name);
+        }
+      }),
+    );
+
+    component
+  }
+}
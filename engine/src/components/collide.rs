@@ -5,31 +5,39 @@ use std::{
 };
 
 use crate::{
-  bowtie::entity::{Component, Entity, Message},
-  general::{direction::Direction, value::Value},
-  math::general::absolute_value_f32,
-  StandardComponent, StandardEntity,
+  bowtie::{component::TypedStore, entity::Entity},
+  general::direction::Direction,
+  ComponentContext, EntityId, StandardComponent, StandardEntity,
 };
 
-pub type CollidingObjects<'d> =
-  HashMap<*mut StandardEntity<'d>, Vec<(*mut StandardEntity<'d>, Direction)>>;
+/// What the collision component keeps for every colliding entity
+#[derive(Debug, Copy, Clone)]
+pub struct CollisionState {
+  pub direction: Direction,
+}
 
-pub type CollidingObjectsArc<'d> = Arc<Mutex<CollidingObjects<'d>>>;
+impl Default for CollisionState {
+  fn default() -> Self {
+    CollisionState {
+      direction: Direction::Stationary,
+    }
+  }
+}
 
 /// Collision Component
 ///
-/// Sends a message reporting the current direction of collision
+/// Reports the current direction of collision
 ///
 /// If an entity has not collided, the `Direction` will be `Direction::Stationary`
 pub struct CollisionComponent<'d> {
-  colliding_objects: CollidingObjectsArc<'d>,
+  states: TypedStore<CollisionState>,
   _marker: PhantomData<&'d i32>,
 }
 
 impl<'d> CollisionComponent<'d> {
   pub fn new() -> CollisionComponent<'d> {
     CollisionComponent {
-      colliding_objects: Arc::new(Mutex::new(HashMap::new())),
+      states: Arc::new(Mutex::new(HashMap::new())),
       _marker: PhantomData,
     }
   }
@@ -98,138 +106,99 @@ impl<'d> CollisionComponent<'d> {
     return direction;
   }
 
-  pub fn get_is_collided(&self, entity_ref: *mut StandardEntity<'d>) -> bool {
-    let is_collided =
-      match self.colliding_objects.lock().unwrap().get(&entity_ref) {
-        Some(collision_vec) => collision_vec.len() > 0,
-        None => false,
-      };
-
-    return is_collided;
+  pub fn get_is_collided(&self, id: EntityId) -> bool {
+    self.get_collision_direction_for(id) != Direction::Stationary
   }
 
-  pub fn get_entity_collision_direction(
-    colliding_objects: &CollidingObjects<'d>,
-    entity_ref: *mut StandardEntity<'d>,
-  ) -> Direction {
-    let mut collision_direction = Direction::Stationary;
-
-    match colliding_objects.get(&entity_ref) {
-      Some(collision_vec) => {
-        for (_, collision_dir) in collision_vec {
-          collision_direction =
-            collision_direction.add_direction(collision_dir.to_owned());
-        }
-      }
-      None => {}
+  /// Last collision direction calculated for the entity
+  pub fn get_collision_direction_for(&self, id: EntityId) -> Direction {
+    match self.states.lock().unwrap().get(&id) {
+      Some(state) => state.direction,
+      None => Direction::Stationary,
     }
-
-    return collision_direction;
   }
 
-  unsafe fn calculate_collision_direction(
-    entity: *const StandardEntity<'d>,
-    other_entity: *const StandardEntity<'d>,
+  /// Last collision direction of an entity with a collision component loaded,
+  /// so other components can react to it
+  pub fn get_entity_collision_direction(
+    entity: &StandardEntity<'d>,
   ) -> Direction {
-    let entity_unwrapped = entity.as_ref().unwrap();
-    let other_entity_unwrapped = other_entity.as_ref().unwrap();
-
-    let other_x = other_entity_unwrapped.get_x();
-    let other_y = other_entity_unwrapped.get_y();
-    let other_height = other_entity_unwrapped.get_height();
-    let other_width = other_entity_unwrapped.get_width();
-
-    let x = entity_unwrapped.get_x();
-    let y = entity_unwrapped.get_y();
-    let height = entity_unwrapped.get_height();
-    let width = entity_unwrapped.get_width();
-
-    let collision_direction = CollisionComponent::get_collision_direction(
-      x,
-      y,
-      width,
-      height,
-      other_x,
-      other_y,
-      other_height,
-      other_width,
-    );
-
-    collision_direction
+    let id = match entity.get_id() {
+      Some(id) => id,
+      None => return Direction::Stationary,
+    };
+
+    entity
+      .get_component(CollisionComponent::get_name().as_str())
+      .and_then(|component| component.get_state::<CollisionState>(id))
+      .map(|state| state.direction)
+      .unwrap_or(Direction::Stationary)
   }
 
-  unsafe fn get_final_collission_direction(
-    colliding_objects: &CollidingObjectsArc<'d>,
-    entity: *mut StandardEntity<'d>,
+  /// Sums up the directions the entity collides with every other entity of
+  /// this collision component, as they were at the start of the update.
+  /// Entities of other collision components are a separate group.
+  fn get_final_collision_direction(
+    states: &HashMap<EntityId, CollisionState>,
+    context: &ComponentContext,
+    entity: &StandardEntity<'d>,
   ) -> Direction {
-    let mut objects = colliding_objects.lock().unwrap();
-
-    let keys = objects
-      .keys()
-      .map(|k| k.to_owned())
-      .collect::<Vec<*mut StandardEntity<'d>>>();
-
-    let current_vec = objects.entry(entity).or_insert(Vec::new());
-    for other_entity in keys {
-      if other_entity.as_ref().unwrap() as *const _
-        == entity.as_ref().unwrap() as *const _
-      {
+    let mut query = context
+      .query()
+      .with_component(CollisionComponent::get_name().as_str())
+      .in_region(
+        entity.get_x(),
+        entity.get_y(),
+        entity.get_width(),
+        entity.get_height(),
+      );
+    if let Some(id) = entity.get_id() {
+      query = query.excluding(id);
+    }
+
+    let mut collision_direction = Direction::Stationary;
+    for other in query.collect() {
+      if !states.contains_key(&other.get_id()) {
         continue;
       }
 
-      let collision_direction =
-        CollisionComponent::calculate_collision_direction(entity, other_entity);
-
-      let other_entity_position = (*current_vec)
-        .iter()
-        .position(|(ex_collided_entity, _)| *ex_collided_entity == other_entity);
-
-      if collision_direction != Direction::Stationary {
-        match other_entity_position {
-          Some(_) => {}
-          None => {
-            (*current_vec).push((other_entity.to_owned(), collision_direction));
-          }
-        }
-      } else {
-        match other_entity_position {
-          Some(pos) => {
-            (*current_vec).remove(pos);
-          }
-          None => {}
-        }
-      }
+      let direction = CollisionComponent::get_collision_direction(
+        entity.get_x(),
+        entity.get_y(),
+        entity.get_width(),
+        entity.get_height(),
+        other.get_x(),
+        other.get_y(),
+        other.get_height(),
+        other.get_width(),
+      );
+
+      collision_direction = collision_direction.add_direction(direction);
     }
 
-    CollisionComponent::get_entity_collision_direction(&objects, entity)
+    collision_direction
   }
 
   pub fn get_name() -> String {
     String::from("collision")
   }
 
-  pub fn component(&'d mut self) -> StandardComponent<'d> {
-    StandardComponent::new(
-      Arc::new(|entity, store| unsafe {
+  pub fn component(&self) -> StandardComponent<'d> {
+    let states = self.states.clone();
+    StandardComponent::with_typed_store(
+      Arc::new(move |entity, state: &mut CollisionState, context| {
         let new_collision_direction =
-          CollisionComponent::get_final_collission_direction(
-            &self.colliding_objects,
+          CollisionComponent::get_final_collision_direction(
+            &states.lock().unwrap(),
+            context,
             entity,
           );
-        let entity_ptr: *const StandardEntity<'d> = entity;
-
-        let direction_value = Value::Number(new_collision_direction.into());
-
-        let mut locked_store = store.lock().unwrap();
-        let dir = locked_store
-          .entry(format!("{:?}", entity_ptr))
-          .or_insert(direction_value.to_owned());
-        *dir = direction_value.to_owned();
 
+        state.direction = new_collision_direction;
         entity.set_collision_direction(new_collision_direction);
       }),
       CollisionComponent::get_name().as_str(),
-      HashMap::new(),
+      self.states.clone(),
     )
   }
 }
@@ -1,22 +1,48 @@
-use std::collections::HashMap;
-
-use crate::bowtie::entity::{Component, Entity, Message};
-
+use std::{
+  collections::HashMap,
+  marker::PhantomData,
+  sync::{Arc, Mutex},
+};
+
+use crate::{
+  bowtie::{
+    entity::{Component, Entity},
+    message::Message,
+  },
+  EntityId, StandardComponent,
+};
+
+/// Event Component
+///
+/// Hands messages pushed for an entity to it, one per update in the order
+/// they were pushed. Copies share their queued messages.
+#[derive(Clone)]
 pub struct EventComponent<'s> {
-  events: HashMap<*mut dyn Entity<'s>, Vec<Message>>,
+  events: Arc<Mutex<HashMap<EntityId, Vec<Message>>>>,
+  _marker: PhantomData<&'s i32>,
 }
 
 impl<'s> EventComponent<'s> {
   pub fn new() -> EventComponent<'s> {
     EventComponent {
-      events: HashMap::new(),
+      events: Arc::new(Mutex::new(HashMap::new())),
+      _marker: PhantomData,
     }
   }
 
-  pub fn push_message(&mut self, entity: *mut dyn Entity<'s>, message: Message) {
-    let event_messages = self.events.entry(entity).or_insert(Vec::new());
+  pub fn push_message(&mut self, entity: EntityId, message: Message) {
+    let mut events = self.events.lock().unwrap();
+    events.entry(entity).or_default().push(message);
+  }
 
-    (*event_messages).push(message);
+  pub fn component(&self) -> StandardComponent<'s> {
+    StandardComponent::from_component(self.clone())
+  }
+}
+
+impl<'s> Default for EventComponent<'s> {
+  fn default() -> Self {
+    EventComponent::new()
   }
 }
 
@@ -25,13 +51,13 @@ impl<'s> Component<'s> for EventComponent<'s> {
     "event"
   }
 
-  unsafe fn act(
-    &mut self,
-    _entities: &Vec<*mut dyn Entity<'s>>,
-    entity: *mut dyn Entity<'s>,
-  ) -> Option<Message> {
-    let event_messages = self.events.entry(entity).or_insert(Vec::new());
+  fn act(&mut self, entity: &mut dyn Entity<'s>) -> Option<Message> {
+    let mut events = self.events.lock().unwrap();
+    let event_messages = events.get_mut(&entity.get_id()?)?;
+    if event_messages.is_empty() {
+      return None;
+    }
 
-    event_messages.pop()
+    Some(event_messages.remove(0))
   }
 }
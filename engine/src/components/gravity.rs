@@ -1,18 +1,17 @@
-use std::{
-  collections::HashMap,
-  marker::PhantomData,
-  sync::{Arc, Mutex},
-};
+use std::{marker::PhantomData, sync::Arc};
 
-use crate::{
-  bowtie::entity::{Component, Entity, Message, StandardEntity},
-  general::value::Value,
-  Direction, StandardComponent,
-};
+use crate::{bowtie::entity::Entity, Direction, StandardComponent};
+
+/// What gravity keeps track of for every falling entity
+#[derive(Debug, Copy, Clone)]
+pub struct FallingObject {
+  pub speed: f32,
+  pub y_position: f32,
+}
 
 /// Gravity Component
 ///
-/// Sends a message about y position updates in `f32`
+/// Drags the entity down, accelerating until it stops falling
 #[derive(Clone)]
 pub struct GravityComponent<'s> {
   speed: f32,
@@ -35,54 +34,32 @@ impl<'s> GravityComponent<'s> {
     String::from("gravity")
   }
 
+  /// The component's state is an `Option<FallingObject>`, which is `None`
+  /// until the entity's first update
   pub fn component(&'s mut self) -> StandardComponent<'s> {
-    StandardComponent::new(
-      Arc::new(|entity, store| {
-        let mut locked_store = store.lock().unwrap();
-
-        let falling_objects = locked_store
-          .entry(String::from("falling_objects"))
-          .or_insert(Value::Object(HashMap::new()));
-
-        match falling_objects {
-          Value::Object(objects) => {
-            let entity_ptr: *mut StandardEntity<'s> = entity;
-            let entity_ptr_str = format!("{:?}", entity_ptr);
+    StandardComponent::typed::<Option<FallingObject>>(
+      Arc::new(|entity, state, _context| {
+        let object = state.get_or_insert(FallingObject {
+          speed: self.speed,
+          y_position: entity.get_y(),
+        });
 
-            let object_info = objects.entry(entity_ptr_str).or_insert(
-              Value::Vec2f32((self.speed.to_owned(), entity.get_y().to_owned())),
-            );
-
-            match object_info {
-              Value::Vec2f32((speed, y_pos)) => {
-                if *speed < self.terminal_velocity {
-                  *speed += self.acceleration;
-                }
-
-                let entity_y = entity.get_y();
+        if object.speed < self.terminal_velocity {
+          object.speed += self.acceleration;
+        }
 
-                // If they have stopped falling, reset their speed.
-                if entity_y == *y_pos {
-                  *speed = self.speed;
-                } else {
-                  *y_pos = entity_y;
-                }
+        let entity_y = entity.get_y();
 
-                entity.move_in_direction(Direction::Down, *speed);
-              }
-              _ => {}
-            }
-          }
-          _ => {
-            panic!("Falling objects should be hashmap")
-          }
+        // If they have stopped falling, reset their speed.
+        if entity_y == object.y_position {
+          object.speed = self.speed;
+        } else {
+          object.y_position = entity_y;
         }
+
+        entity.move_in_direction(Direction::Down, object.speed);
       }),
       GravityComponent::get_name().as_str(),
-      HashMap::from([(
-        String::from("falling_objects"),
-        Value::Object(HashMap::new()),
-      )]),
     )
   }
 }
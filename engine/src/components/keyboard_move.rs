@@ -5,8 +5,8 @@ use std::{
 };
 
 use crate::{
-  general::value::Value, premade_components::CollisionComponent, Direction,
-  StandardComponent, StandardEntity,
+  premade_components::CollisionComponent, Direction, StandardComponent,
+  StandardEntity,
 };
 
 #[derive(Clone)]
@@ -52,26 +52,9 @@ impl<'s> KeyboardMoveComponent<'s> {
       return;
     }
 
-    match entity.get_component(CollisionComponent::get_name().as_str()) {
-      Some(comp) => {
-        let collision_store = comp.get_store().lock().unwrap();
-        let entity_ptr: *const StandardEntity<'s> = entity;
-        let entity_id = format!("{:?}", entity_ptr);
-        match collision_store.get(&entity_id) {
-          None => {}
-          Some(dir_num) => {
-            match dir_num {
-              Value::Number(num) => {
-                let collision_direction = Direction::from(num.clone());
-                direction = direction.subtract_direction(collision_direction);
-              }
-              _ => {}
-            }
-          }
-        }
-      }
-      None => {}
-    }
+    let collision_direction =
+      CollisionComponent::get_entity_collision_direction(entity);
+    direction = direction.subtract_direction(collision_direction);
 
     entity.move_in_direction(direction, speed_clone);
 
@@ -129,7 +112,7 @@ impl<'s> KeyboardMoveComponent<'s> {
 
   pub fn component(&'s self) -> StandardComponent<'s> {
     StandardComponent::new(
-      Arc::new(|entity, _store| {
+      Arc::new(|entity, _store, _context| {
         KeyboardMoveComponent::move_component(
           entity,
           &self.speed,
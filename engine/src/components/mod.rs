@@ -1,3 +1,4 @@
+pub mod animation;
 pub mod collide;
 pub mod event;
 pub mod gravity;
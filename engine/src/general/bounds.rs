@@ -0,0 +1,129 @@
+use super::direction::Direction;
+
+/// What happens to an entity that moves past its bounds
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BoundsBehavior {
+  /// Stops at the edge
+  Clamp,
+  /// Comes back in from the opposite edge once it's fully out
+  Wrap,
+  /// Gets despawned once it's fully out
+  Despawn,
+}
+
+/// Area entities are allowed to move in.
+/// Like shapes, it hangs down and to the right from `(x, y)`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Bounds {
+  pub x: f32,
+  pub y: f32,
+  pub width: f32,
+  pub height: f32,
+  pub behavior: BoundsBehavior,
+}
+
+impl Bounds {
+  pub fn new(
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    behavior: BoundsBehavior,
+  ) -> Bounds {
+    Bounds {
+      x,
+      y,
+      width,
+      height,
+      behavior,
+    }
+  }
+
+  /// Bounds covering OpenGL's -1.0 to 1.0 coordinates, which is what the
+  /// default camera shows
+  pub fn screen(behavior: BoundsBehavior) -> Bounds {
+    Bounds::new(-1.0, 1.0, 2.0, 2.0, behavior)
+  }
+
+  pub fn get_left(&self) -> f32 {
+    self.x
+  }
+
+  pub fn get_right(&self) -> f32 {
+    self.x + self.width
+  }
+
+  pub fn get_top(&self) -> f32 {
+    self.y
+  }
+
+  pub fn get_bottom(&self) -> f32 {
+    self.y - self.height
+  }
+
+  /// Where a box at `(x, y)` of the given size ends up after applying the
+  /// bounds, along with the edges it went past.
+  ///
+  /// `Clamp` reports an edge as soon as the box pokes out of it, `Wrap` and
+  /// `Despawn` only once the box is fully out. `Despawn` never moves the box,
+  /// removing the entity is up to the caller.
+  pub fn confine(
+    &self,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+  ) -> (f32, f32, Direction) {
+    let (mut new_x, mut new_y) = (x, y);
+    let mut hit = Direction::Stationary;
+
+    match self.behavior {
+      BoundsBehavior::Clamp => {
+        if x < self.get_left() {
+          new_x = self.get_left();
+          hit = hit.add_direction(Direction::Left);
+        } else if x + width > self.get_right() {
+          new_x = self.get_right() - width;
+          hit = hit.add_direction(Direction::Right);
+        }
+
+        if y > self.get_top() {
+          new_y = self.get_top();
+          hit = hit.add_direction(Direction::Up);
+        } else if y - height < self.get_bottom() {
+          new_y = self.get_bottom() + height;
+          hit = hit.add_direction(Direction::Down);
+        }
+      }
+      BoundsBehavior::Wrap | BoundsBehavior::Despawn => {
+        let wraps = self.behavior == BoundsBehavior::Wrap;
+
+        if x + width <= self.get_left() {
+          if wraps {
+            new_x = x + self.width + width;
+          }
+          hit = hit.add_direction(Direction::Left);
+        } else if x >= self.get_right() {
+          if wraps {
+            new_x = x - self.width - width;
+          }
+          hit = hit.add_direction(Direction::Right);
+        }
+
+        if y - height >= self.get_top() {
+          if wraps {
+            new_y = y - self.height - height;
+          }
+          hit = hit.add_direction(Direction::Up);
+        } else if y <= self.get_bottom() {
+          if wraps {
+            new_y = y + self.height + height;
+          }
+          hit = hit.add_direction(Direction::Down);
+        }
+      }
+    }
+
+    (new_x, new_y, hit)
+  }
+}
@@ -0,0 +1,50 @@
+use std::{
+  collections::HashMap,
+  fs,
+  path::{Path, PathBuf},
+  time::SystemTime,
+};
+
+/// Remembers when files were last modified, to tell which ones changed
+/// since. Files that don't exist (yet) count as changed once they show up.
+#[derive(Debug, Clone, Default)]
+pub struct FileWatcher {
+  modified: HashMap<PathBuf, Option<SystemTime>>,
+}
+
+fn get_modified(path: &Path) -> Option<SystemTime> {
+  fs::metadata(path)
+    .and_then(|metadata| metadata.modified())
+    .ok()
+}
+
+impl FileWatcher {
+  pub fn new() -> FileWatcher {
+    FileWatcher {
+      modified: HashMap::new(),
+    }
+  }
+
+  /// Starts watching the file. Files that are already watched are skipped,
+  /// so changes made since they were first watched aren't lost.
+  pub fn watch(&mut self, path: &Path) {
+    if !self.modified.contains_key(path) {
+      self.modified.insert(path.to_path_buf(), get_modified(path));
+    }
+  }
+
+  /// Files whose modification time changed since the last poll
+  pub fn poll(&mut self) -> Vec<PathBuf> {
+    let mut changed = vec![];
+    for (path, modified) in self.modified.iter_mut() {
+      let now = get_modified(path);
+      if now != *modified {
+        *modified = now;
+        if now.is_some() {
+          changed.push(path.to_owned());
+        }
+      }
+    }
+    changed
+  }
+}
@@ -1,3 +1,5 @@
+pub mod bounds;
 pub mod color;
 pub mod direction;
+pub mod file_watcher;
 pub mod value;
@@ -40,7 +40,7 @@ impl<T> ElementArrayBuffer<T> {
     };
   }
 
-  pub fn update_data(&self, elements: &Vec<T>) {
+  pub fn update_data(&self, elements: &[T]) {
     unsafe {
       gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.id);
       gl::BufferData(
@@ -67,6 +67,10 @@ impl<T> ElementArrayBuffer<T> {
 
 impl<T> Drop for ElementArrayBuffer<T> {
   fn drop(&mut self) {
+    // Shells never generated a buffer
+    if self.id == 0 {
+      return;
+    }
     unsafe { gl::DeleteBuffers(1, &self.id) };
   }
 }
@@ -7,6 +7,8 @@ pub enum GlErrorResult {
 
 pub enum GlError {
   ShaderError(u32),
+  /// Linking errors of a program
+  ProgramError(u32),
 }
 
 pub fn pull_errors() {
@@ -55,6 +57,22 @@ pub fn get_error(error: GlError, max_length: i32) -> GlErrorResult {
         return GlErrorResult::NoError;
       }
     },
+    GlError::ProgramError(program_id) => unsafe {
+      let mut status = -1;
+      gl::GetProgramiv(program_id, gl::LINK_STATUS, &mut status);
+
+      if (status as u8) != gl::TRUE {
+        gl::GetProgramInfoLog(
+          program_id,
+          max_length,
+          &mut buffer_len,
+          &mut buffer[0],
+        );
+        return GlErrorResult::Error(buffer_to_string(&buffer));
+      } else {
+        return GlErrorResult::NoError;
+      }
+    },
   }
 }
 
@@ -1,19 +1,26 @@
 extern crate gl;
 extern crate png;
 
-use std::collections::HashMap;
-use std::fs::File;
+use std::{
+  path::{Path, PathBuf},
+  sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc, RwLock, Weak,
+  },
+};
 
-use super::shader_creator::ShaderProgram;
-
-use super::uniform::{SettableUniform, UniformInteger};
+use super::image_data::{ImageData, ImageError};
 
 use super::gl_translation::{TextureFilter, TextureWrap, ToGl};
 
-static mut TEXTURE_COUNT: u32 = 0;
+/// Hands out the ids that tell textures apart. Ids are not texture units,
+/// units are assigned per draw call by the `Drawer`.
+static TEXTURE_COUNT: AtomicU32 = AtomicU32::new(0);
 
 pub trait LoadableTexture {
-  fn load_texture(&self, program: &ShaderProgram);
+  /// Uploads the texture to the gl state machine and returns the
+  /// generated gl texture name. Image files are looked up in `asset_root`.
+  fn load_texture(&self, asset_root: &Path) -> Result<u32, ImageError>;
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -24,15 +31,55 @@ pub struct TextureOptions {
 }
 
 #[derive(Debug, Clone)]
-/// OpenGL texture. Upon creation, it defines a sampler uniform and initializes
+/// OpenGL texture. Upon loading, it defines a sampler uniform and initializes
 /// a texture buffer, loading it with wit the file's data.
+/// Textures either point at `images/{image_name}.png` under the asset root,
+/// or hold their pixels in memory, see `from_bytes` and `from_rgba`.
 /// Current supported format(s): png
+///
+/// Copies of a texture share it rather than loading it again. Once the last
+/// copy is dropped, e.g. when the last sprite using it is unloaded, the
+/// `Drawer` has the render backend release it.
 pub struct Texture {
   pub texture_id: i32,
   pub image_name: String,
-  id: i32,
   options: TextureOptions,
-  is_from_ref: bool,
+  /// Shared by every copy, `None` for `Texture::none()`
+  handle: Option<Arc<TextureHandle>>,
+}
+
+/// Where a texture's pixels come from
+#[derive(Debug)]
+enum TextureSource {
+  /// `images/{image_name}.png` under the asset root
+  File,
+  /// Pixels already in memory
+  Image(ImageData),
+  /// Still being decoded by an `AssetServer`, the placeholder is drawn
+  /// instead until then
+  Pending(Texture),
+  /// An `AssetServer` couldn't decode the image, the placeholder is drawn
+  /// instead
+  Failed(Texture),
+}
+
+/// What copies of a texture share
+#[derive(Debug)]
+pub(crate) struct TextureHandle {
+  source: RwLock<TextureSource>,
+  /// Whether the pixels come from the image file, even if they're in memory
+  /// by now
+  from_file: bool,
+}
+
+impl TextureHandle {
+  /// Reads the image file again, replacing the pixels of every copy of the
+  /// texture. They keep their old pixels if it fails.
+  pub(crate) fn reload_image(&self, location: &Path) -> Result<(), ImageError> {
+    let image = ImageData::from_png_file(location)?;
+    *self.source.write().unwrap() = TextureSource::Image(image);
+    Ok(())
+  }
 }
 
 impl Default for TextureOptions {
@@ -50,109 +97,229 @@ impl Default for TextureOptions {
 
 impl From<&Texture> for Texture {
   /*
-   * Loads texture from texutre reference
-   * NOTE: Texture has to be loaded before hand
+   * Copies the texture, sharing it with the original
    */
   fn from(texture_ref: &Texture) -> Self {
-    Texture {
-      texture_id: texture_ref.texture_id,
-      id: texture_ref.id,
-      options: texture_ref.options,
-      image_name: String::from(texture_ref.image_name.as_str()),
-      is_from_ref: true,
-    }
+    texture_ref.to_owned()
   }
 }
 
 impl Texture {
   /// Creates a new texture ready to be loaded
-  /// It will generate a texture buffer on the gl state machine
+  /// The gl texture buffer is only generated once the texture is loaded
   pub fn new(image_name: &str, options: TextureOptions) -> Texture {
-    unsafe {
-      let mut id: u32 = 0;
-      gl::GenTextures(1, &mut id);
+    Texture::with_source(image_name, TextureSource::File, options)
+  }
+
+  fn with_source(
+    image_name: &str,
+    source: TextureSource,
+    options: TextureOptions,
+  ) -> Texture {
+    let from_file = !matches!(source, TextureSource::Image(_));
+
+    // Incrementing texture count to have accurate Texture ID
+    let texture_id = TEXTURE_COUNT.fetch_add(1, Ordering::SeqCst);
+
+    // Create a texture that is ready to be loaded
+    Texture {
+      texture_id: texture_id as i32,
+      options,
+      image_name: String::from(image_name),
+      handle: Some(Arc::new(TextureHandle {
+        source: RwLock::new(source),
+        from_file,
+      })),
+    }
+  }
+
+  /// Creates a texture out of pixels that are already in memory, e.g. a
+  /// texture atlas page. `name` only identifies the texture.
+  pub fn from_image_data(
+    name: &str,
+    image: ImageData,
+    options: TextureOptions,
+  ) -> Texture {
+    Texture::with_source(name, TextureSource::Image(image), options)
+  }
+
+  /// Texture whose pixels are still on their way, drawn as `placeholder`
+  /// until `finish_loading` is called
+  pub(crate) fn pending(
+    image_name: &str,
+    options: TextureOptions,
+    placeholder: Texture,
+  ) -> Texture {
+    Texture::with_source(
+      image_name,
+      TextureSource::Pending(placeholder),
+      options,
+    )
+  }
 
-      // Create a texture that is ready to be loaded
-      let tex = Texture {
-        texture_id: TEXTURE_COUNT as i32,
-        id: id as i32,
-        options,
-        image_name: String::from(image_name),
-        is_from_ref: false,
-      };
+  /// Hands every copy of the texture its decoded pixels
+  pub(crate) fn finish_loading(&self, image: ImageData) {
+    if let Some(handle) = &self.handle {
+      *handle.source.write().unwrap() = TextureSource::Image(image);
+    }
+  }
+
+  /// Stops waiting for pixels that couldn't be decoded, every copy of the
+  /// texture shows its placeholder from now on
+  pub(crate) fn fail_loading(&self) {
+    if let Some(handle) = &self.handle {
+      let mut source = handle.source.write().unwrap();
+      if let TextureSource::Pending(placeholder) = &*source {
+        *source = TextureSource::Failed(placeholder.to_owned());
+      }
+    }
+  }
+
+  /// Whether the texture's pixels come from its image file under the asset
+  /// root, rather than from memory
+  pub fn is_from_file(&self) -> bool {
+    self
+      .handle
+      .as_ref()
+      .map_or(false, |handle| handle.from_file)
+  }
 
-      // Incrementing texture count to have accurate Texture ID
-      TEXTURE_COUNT = TEXTURE_COUNT + 1;
+  /// Whether the texture's pixels are still being decoded
+  pub fn is_loading(&self) -> bool {
+    self.handle.as_ref().map_or(false, |handle| {
+      matches!(*handle.source.read().unwrap(), TextureSource::Pending(_))
+    })
+  }
 
-      return tex;
+  /// Texture to draw in place of this one, its placeholder while it's
+  /// loading or if it failed to, and itself otherwise
+  pub(crate) fn resolve(&self) -> Texture {
+    if let Some(handle) = &self.handle {
+      match &*handle.source.read().unwrap() {
+        TextureSource::Pending(placeholder)
+        | TextureSource::Failed(placeholder) => return placeholder.to_owned(),
+        _ => {}
+      }
     }
+    self.to_owned()
+  }
+
+  /// Creates a texture out of an encoded png, e.g. one embedded with
+  /// `include_bytes!`. The png is decoded right away.
+  pub fn from_bytes(
+    name: &str,
+    bytes: &[u8],
+    options: TextureOptions,
+  ) -> Result<Texture, ImageError> {
+    let image = ImageData::from_png(bytes)?;
+    Ok(Texture::from_image_data(name, image, options))
+  }
+
+  /// Creates a texture out of RGBA8 pixels, rows from top to bottom, e.g. a
+  /// procedurally generated image
+  pub fn from_rgba(
+    name: &str,
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+    options: TextureOptions,
+  ) -> Result<Texture, ImageError> {
+    let image = ImageData::from_rgba(width, height, pixels)?;
+    Ok(Texture::from_image_data(name, image, options))
+  }
+
+  pub fn get_options(&self) -> TextureOptions {
+    self.options
+  }
+
+  /// Number of copies of the texture that are alive, this one included
+  pub fn get_reference_count(&self) -> usize {
+    self.handle.as_ref().map_or(0, Arc::strong_count)
+  }
+
+  /// Reference that can tell once every copy of the texture is dropped
+  pub(crate) fn downgrade(&self) -> Option<Weak<TextureHandle>> {
+    self.handle.as_ref().map(Arc::downgrade)
   }
 
   /// Function used to denote that lack of texture
   pub fn none() -> Texture {
     Texture {
       texture_id: -1,
-      id: -1,
       options: TextureOptions::default(),
       image_name: String::from(""),
-      is_from_ref: true,
+      handle: None,
     }
   }
 
-  fn get_image_location(image_name: &str) -> String {
-    return format!("./images/{image_name}.png");
+  /// Where the texture's image file is under `asset_root`
+  pub fn get_image_location(&self, asset_root: &Path) -> PathBuf {
+    asset_root
+      .join("images")
+      .join(format!("{}.png", self.image_name))
   }
 
-  /// Sets the sampler fragment shader Uniform
-  pub fn set_uniform(&self, program: &ShaderProgram) {
-    if self.texture_id == -1 {
-      return;
+  /// Decodes the texture's image into RGBA8 pixels.
+  /// In memory textures ignore `asset_root`.
+  pub fn load_image_data(
+    &self,
+    asset_root: &Path,
+  ) -> Result<ImageData, ImageError> {
+    let location = self.get_image_location(asset_root);
+    match &self.handle {
+      Some(handle) => match &*handle.source.read().unwrap() {
+        // Failed images are read again, in case the file was fixed since
+        TextureSource::File | TextureSource::Failed(_) => {
+          ImageData::from_png_file(&location)
+        }
+        TextureSource::Image(image) => Ok(image.to_owned()),
+        TextureSource::Pending(_) => {
+          Err(ImageError::StillLoading(self.image_name.to_owned()))
+        }
+      },
+      None => ImageData::from_png_file(&location),
     }
-    let uniform = UniformInteger::new(
-      format!("tex{}_sampler", self.texture_id).as_str(),
-      self.texture_id,
-    );
-    program.set_uniform(&uniform);
   }
 }
 
 impl LoadableTexture for Texture {
-  fn load_texture(&self, program: &ShaderProgram) {
+  fn load_texture(&self, asset_root: &Path) -> Result<u32, ImageError> {
     if self.texture_id < 0 {
-      return;
+      return Ok(0);
     }
+
+    // Loading file bytes
+    let image = self.load_image_data(asset_root)?;
+
+    let mut id: u32 = 0;
     unsafe {
-      // BindTexture requires a specific texture to be activated first
-      gl::ActiveTexture(gl::TEXTURE0 + (self.texture_id as u32));
-      gl::BindTexture(gl::TEXTURE_2D, self.id as u32);
+      gl::GenTextures(1, &mut id);
 
-      // Loading file bytes
-      let decoder = png::Decoder::new(
-        File::open(Texture::get_image_location(&self.image_name)).unwrap(),
-      );
+      // Uploading through the first unit, the drawer binds textures to their
+      // actual units before every draw call
+      gl::ActiveTexture(gl::TEXTURE0);
+      gl::BindTexture(gl::TEXTURE_2D, id);
 
-      let (info, mut reader) = decoder.read_info().unwrap();
-      let mut buf = vec![0; info.buffer_size()];
-      reader.next_frame(&mut buf).unwrap();
+      // Pixels are always tightly packed RGBA8, but gl defaults to expecting
+      // rows aligned to 4 bytes
+      gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
 
       // Loading image into gl
       gl::TexImage2D(
         gl::TEXTURE_2D,
         0,
-        info.color_type.to_gl() as i32,
-        info.width as i32,
-        info.height as i32,
+        gl::RGBA as i32,
+        image.width as i32,
+        image.height as i32,
         0,
-        info.color_type.to_gl(),
+        gl::RGBA,
         gl::UNSIGNED_BYTE,
-        buf.as_ptr() as *const gl::types::GLvoid,
+        image.pixels.as_ptr() as *const gl::types::GLvoid,
       );
 
       // Using mipmaps for performance
       gl::GenerateMipmap(gl::TEXTURE_2D);
 
-      self.set_uniform(program);
-
       // Wrap
       gl::TexParameteri(
         gl::TEXTURE_2D,
@@ -177,5 +344,6 @@ impl LoadableTexture for Texture {
         self.options.min_filter.to_gl() as i32,
       );
     };
+    Ok(id)
   }
 }
@@ -1,11 +1,11 @@
 use crate::{LoadableTexture, Texture};
-use std::collections::HashMap;
+use std::{collections::HashMap, path::Path};
 
-use super::shader_creator::ShaderProgram;
-
-#[derive(Clone)]
+/// Owns the gl textures it uploads, deleting them once they're released or
+/// the loader is dropped
 pub struct TextureLoader {
-  loaded_textures: HashMap<u32, bool>,
+  /// Texture id to the gl texture name it was uploaded to
+  loaded_textures: HashMap<u32, u32>,
 }
 
 impl TextureLoader {
@@ -14,37 +14,71 @@ impl TextureLoader {
       loaded_textures: HashMap::new(),
     }
   }
-  pub fn load_texture(&mut self, texture: Texture, program: &ShaderProgram) {
+  /// Textures that fail to load are reported once and then drawn without
+  /// their image
+  pub fn load_texture(&mut self, texture: Texture, asset_root: &Path) {
     if texture.texture_id < 0 {
       return;
     }
 
-    let entry = self
-      .loaded_textures
-      .entry(texture.texture_id as u32)
-      .or_insert(false);
-
-    if !*entry {
-      texture.load_texture(program);
-      *entry = true;
+    if !self.loaded_textures.contains_key(&(texture.texture_id as u32)) {
+      let gl_name = match texture.load_texture(asset_root) {
+        Ok(gl_name) => gl_name,
+        Err(error) => {
+          eprintln!("Could not load texture {:?}: {error}", texture.image_name);
+          0
+        }
+      };
+      self
+        .loaded_textures
+        .insert(texture.texture_id as u32, gl_name);
     }
   }
-  pub fn load_textures(
-    &mut self,
-    textures: Vec<Texture>,
-    program: &ShaderProgram,
-  ) {
+  pub fn load_textures(&mut self, textures: &[Texture], asset_root: &Path) {
     for texture in textures {
-      self.load_texture(texture.to_owned(), &program);
+      self.load_texture(texture.to_owned(), asset_root);
+    }
+  }
+
+  /// Binds every texture to the unit matching its index.
+  /// Textures that weren't loaded yet are skipped.
+  pub fn bind_textures(&self, textures: &[Texture]) {
+    for (unit, texture) in textures.iter().enumerate() {
+      if texture.texture_id < 0 {
+        continue;
+      }
+      if let Some(gl_name) =
+        self.loaded_textures.get(&(texture.texture_id as u32))
+      {
+        unsafe {
+          gl::ActiveTexture(gl::TEXTURE0 + unit as u32);
+          gl::BindTexture(gl::TEXTURE_2D, *gl_name);
+        }
+      }
     }
   }
+
+  /// Deletes the gl texture of every texture id
+  pub fn release_textures(&mut self, texture_ids: &[i32]) {
+    for texture_id in texture_ids {
+      if let Some(gl_name) = self.loaded_textures.remove(&(*texture_id as u32)) {
+        delete_texture(gl_name);
+      }
+    }
+  }
+}
+
+/// Textures that failed to load have no gl texture to delete
+fn delete_texture(gl_name: u32) {
+  if gl_name != 0 {
+    unsafe { gl::DeleteTextures(1, &gl_name) };
+  }
 }
 
 impl Drop for TextureLoader {
   fn drop(&mut self) {
-    println!("Dropping loader");
-    for texture in self.loaded_textures.keys() {
-      //unsafe { gl::DeleteTextures(1, texture) };
+    for gl_name in self.loaded_textures.values() {
+      delete_texture(*gl_name);
     }
   }
 }
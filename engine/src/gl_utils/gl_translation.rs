@@ -1,4 +1,3 @@
-extern crate png;
 use std::{fmt::Display, mem};
 
 pub trait ToGl {
@@ -42,6 +41,7 @@ impl ToGl for DataType {
   }
 }
 
+#[derive(Copy, Clone, Debug)]
 pub enum DrawingMode {
   Triangles,
 }
@@ -67,16 +67,6 @@ impl ToGl for UsageMode {
   }
 }
 
-impl ToGl for png::ColorType {
-  fn to_gl(&self) -> u32 {
-    match self {
-      png::ColorType::RGBA => gl::RGBA,
-      png::ColorType::RGB => gl::RGB,
-      _ => panic!("PNG Color format {:?} not supported", self),
-    }
-  }
-}
-
 #[derive(Debug, Copy, Clone)]
 pub enum TextureWrap {
   ClampToEdge,
@@ -0,0 +1,192 @@
+extern crate png;
+
+use std::{
+  error::Error,
+  fmt::{self, Display},
+  fs::File,
+  io::{self, Read, Write},
+  path::{Path, PathBuf},
+};
+
+/// Why an image couldn't be loaded
+#[derive(Debug)]
+pub enum ImageError {
+  /// The file couldn't be opened or read
+  Io { path: PathBuf, error: io::Error },
+  /// The bytes aren't a valid png
+  Decode(png::DecodingError),
+  /// Valid png in a format that can't be converted to RGBA8
+  UnsupportedFormat(String),
+  /// The image is still being decoded by an `AssetServer`
+  StillLoading(String),
+  /// Pixel data that doesn't fit the image's size
+  SizeMismatch {
+    width: u32,
+    height: u32,
+    length: usize,
+  },
+}
+
+impl Display for ImageError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ImageError::Io { path, error } => {
+        write!(f, "Could not read image {}: {error}", path.display())
+      }
+      ImageError::Decode(error) => write!(f, "Could not decode png: {error}"),
+      ImageError::UnsupportedFormat(format) => {
+        write!(f, "PNG format {format} not supported")
+      }
+      ImageError::StillLoading(name) => {
+        write!(f, "Image {name:?} is still loading")
+      }
+      ImageError::SizeMismatch {
+        width,
+        height,
+        length,
+      } => write!(
+        f,
+        "{length} bytes of pixel data do not match a {width}x{height} RGBA image"
+      ),
+    }
+  }
+}
+
+impl Error for ImageError {}
+
+impl From<png::DecodingError> for ImageError {
+  fn from(error: png::DecodingError) -> Self {
+    ImageError::Decode(error)
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+/// Decoded image, stored as tightly packed RGBA8 rows from top to bottom.
+pub struct ImageData {
+  pub width: u32,
+  pub height: u32,
+  pub pixels: Vec<u8>,
+}
+
+impl ImageData {
+  pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> ImageData {
+    assert!(
+      ImageData::byte_len(width, height) == Some(pixels.len()),
+      "Pixel data does not match a {}x{} RGBA image",
+      width,
+      height
+    );
+    ImageData {
+      width,
+      height,
+      pixels,
+    }
+  }
+
+  /// Bytes of a `width` by `height` RGBA image, `None` if that doesn't fit
+  /// in memory
+  fn byte_len(width: u32, height: u32) -> Option<usize> {
+    (width as usize)
+      .checked_mul(height as usize)?
+      .checked_mul(4)
+  }
+
+  /// Like `new`, but returns an error instead of panicking when the pixels
+  /// don't match the size
+  pub fn from_rgba(
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+  ) -> Result<ImageData, ImageError> {
+    if ImageData::byte_len(width, height) != Some(pixels.len()) {
+      return Err(ImageError::SizeMismatch {
+        width,
+        height,
+        length: pixels.len(),
+      });
+    }
+    Ok(ImageData::new(width, height, pixels))
+  }
+
+  /// Decodes a png from any reader into RGBA8.
+  /// Grayscale, indexed, 16-bit and interlaced pngs are all converted, with
+  /// transparent palette and grayscale entries turned into alpha.
+  pub fn from_png<R: Read>(reader: R) -> Result<ImageData, ImageError> {
+    use png::HasParameters;
+
+    let mut decoder = png::Decoder::new(reader);
+    // Palettes and bit depths under 8 are expanded, 16-bit is cut down to 8
+    decoder.set(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+
+    let (info, mut reader) = decoder.read_info()?;
+    let mut buf = vec![0; info.buffer_size()];
+    reader.next_frame(&mut buf)?;
+
+    let channels = match (info.color_type, info.bit_depth) {
+      (png::ColorType::Grayscale, png::BitDepth::Eight) => 1,
+      (png::ColorType::GrayscaleAlpha, png::BitDepth::Eight) => 2,
+      (png::ColorType::RGB, png::BitDepth::Eight) => 3,
+      (png::ColorType::RGBA, png::BitDepth::Eight) => 4,
+      (color_type, bit_depth) => {
+        return Err(ImageError::UnsupportedFormat(format!(
+          "{color_type:?} at {} bits",
+          bit_depth as u8
+        )))
+      }
+    };
+
+    // Rows are only as long as their pixels, but reading them through the
+    // decoder's line size keeps any padding out of the pixels
+    let row_length = info.width as usize * channels;
+    let mut pixels = Vec::with_capacity((info.width * info.height * 4) as usize);
+    for row in buf.chunks(info.line_size.max(1)).take(info.height as usize) {
+      for pixel in row[..row_length].chunks(channels) {
+        pixels.extend_from_slice(&match pixel {
+          [gray] => [*gray, *gray, *gray, 255],
+          [gray, alpha] => [*gray, *gray, *gray, *alpha],
+          [r, g, b] => [*r, *g, *b, 255],
+          [r, g, b, a] => [*r, *g, *b, *a],
+          _ => unreachable!(),
+        });
+      }
+    }
+
+    ImageData::from_rgba(info.width, info.height, pixels)
+  }
+
+  pub fn from_png_file(location: &Path) -> Result<ImageData, ImageError> {
+    let file = File::open(location).map_err(|error| ImageError::Io {
+      path: location.to_path_buf(),
+      error,
+    })?;
+    ImageData::from_png(file)
+  }
+
+  /// Returns the RGBA value at x, y. Coordinates are clamped to the edges.
+  pub fn get_pixel(&self, x: u32, y: u32) -> [u8; 4] {
+    let x = x.min(self.width - 1);
+    let y = y.min(self.height - 1);
+    let idx = ((y * self.width + x) * 4) as usize;
+    [
+      self.pixels[idx],
+      self.pixels[idx + 1],
+      self.pixels[idx + 2],
+      self.pixels[idx + 3],
+    ]
+  }
+
+  /// Encodes the image as an RGBA png
+  pub fn write_png<W: Write>(&self, writer: W) -> std::io::Result<()> {
+    use png::HasParameters;
+
+    let mut encoder = png::Encoder::new(writer, self.width, self.height);
+    encoder.set(png::ColorType::RGBA).set(png::BitDepth::Eight);
+    let mut png_writer = encoder.write_header()?;
+    png_writer.write_image_data(&self.pixels)?;
+    Ok(())
+  }
+
+  pub fn save_png(&self, location: &str) -> std::io::Result<()> {
+    self.write_png(File::create(location)?)
+  }
+}
@@ -3,7 +3,9 @@ pub mod gl_error_reader;
 pub mod gl_texture;
 pub mod gl_texture_loader;
 pub mod gl_translation;
+pub mod image_data;
 pub mod shader_creator;
+pub mod texture_atlas;
 pub mod uniform;
 pub mod vertex_array_buffer;
 pub mod vertex_array_object_handler;
@@ -1,6 +1,11 @@
 extern crate gl;
 
-use std::{collections::HashMap, ffi::CString, fs};
+use std::{
+  collections::HashMap,
+  ffi::CString,
+  fs,
+  path::{Path, PathBuf},
+};
 
 use super::gl_error_reader::{GlError, GlErrorResult};
 use super::gl_translation::{DataType, ToGl};
@@ -62,6 +67,16 @@ impl Shader {
       Shader::FragmentShader(name) => name.to_owned(),
     };
   }
+
+  /// Path of the shader's source relative to the shader root
+  pub fn get_file_name(&self) -> String {
+    match self {
+      Shader::VertexShader(_name, _attributes) => {
+        format!("vertex/{}.glsl", self.get_name())
+      }
+      Shader::FragmentShader(_) => format!("fragment/{}.glsl", self.get_name()),
+    }
+  }
 }
 
 fn get_c_string(original_string: String) -> CString {
@@ -72,6 +87,24 @@ fn get_c_string(original_string: String) -> CString {
 pub struct ShaderProgram {
   pub program_id: u32,
   shader_map: HashMap<u32, Shader>,
+  /// Generated source pasted in place of `#include "<name>"` lines
+  includes: HashMap<String, String>,
+  /// Sources handed over in memory, by shader file name
+  sources: HashMap<String, String>,
+  /// Directory with the `vertex` and `fragment` shader directories, the
+  /// built-in shaders are used if there is none
+  shader_root: Option<PathBuf>,
+}
+
+/// Source of the engine's own shaders, compiled into the binary
+fn get_builtin_source(shader: &Shader) -> Option<&'static str> {
+  match shader.get_file_name().as_str() {
+    "vertex/main.glsl" => Some(include_str!("../../shaders/vertex/main.glsl")),
+    "fragment/main.glsl" => {
+      Some(include_str!("../../shaders/fragment/main.glsl"))
+    }
+    _ => None,
+  }
 }
 
 impl ShaderProgram {
@@ -79,6 +112,9 @@ impl ShaderProgram {
     ShaderProgram {
       program_id: 0,
       shader_map: HashMap::new(),
+      includes: HashMap::new(),
+      sources: HashMap::new(),
+      shader_root: None,
     }
   }
   pub fn new() -> ShaderProgram {
@@ -86,6 +122,9 @@ impl ShaderProgram {
       ShaderProgram {
         program_id: gl::CreateProgram(),
         shader_map: HashMap::new(),
+        includes: HashMap::new(),
+        sources: HashMap::new(),
+        shader_root: None,
       }
     }
   }
@@ -103,39 +142,101 @@ impl ShaderProgram {
     uniform.set_uniform(uniform_location);
   }
 
-  fn get_shader_location(&self, shader: &Shader) -> String {
-    let mut base_url = String::from(concat!(env!("OUT_DIR"), "/shaders/"));
-    match shader {
-      Shader::VertexShader(_name, _attributes) => {
-        base_url.push_str("vertex/");
-      }
-      Shader::FragmentShader(_) => {
-        base_url.push_str("fragment/");
-      }
+  /// Sets the uniform if the program has it, uniforms the shaders don't
+  /// use are optimized out by the driver.
+  /// Returns whether the uniform was found.
+  pub fn set_optional_uniform<T>(
+    &self,
+    uniform: &dyn SettableUniform<T>,
+  ) -> bool {
+    let uniform_name = get_c_string(uniform.get_name().to_owned());
+
+    let uniform_location =
+      unsafe { gl::GetUniformLocation(self.program_id, uniform_name.as_ptr()) };
+
+    if uniform_location < 0 {
+      return false;
     }
 
-    base_url.push_str(&shader.get_name());
-    base_url.push_str(".glsl");
+    uniform.set_uniform(uniform_location);
+    true
+  }
 
-    return base_url;
+  /// Source to paste in place of `#include "<name>"` in the shaders loaded
+  /// after this call, for code that's generated at runtime
+  pub fn set_include(&mut self, name: &str, source: String) {
+    self.includes.insert(String::from(name), source);
   }
 
-  fn load_shader_src(&self, shader: &Shader, id: u32) {
+  /// Reads the shaders loaded after this call from files under
+  /// `shader_root` instead of using the built-in ones
+  pub fn set_shader_root(&mut self, shader_root: &Path) {
+    self.shader_root = Some(shader_root.to_path_buf());
+  }
+
+  /// Source of the shader with this file name, see `Shader::get_file_name`.
+  /// Used instead of the built-in shader or its file.
+  pub fn set_source(&mut self, file_name: &str, source: String) {
+    self.sources.insert(String::from(file_name), source);
+  }
+
+  pub fn get_shaders(&self) -> Vec<&Shader> {
+    self.shader_map.values().collect()
+  }
+
+  /// Where the shader is read from, `built-in/<file name>` for built-in
+  /// shaders and just the file name for sources set with `set_source`
+  fn get_shader_location(&self, shader: &Shader) -> PathBuf {
+    let file_name = shader.get_file_name();
+    if self.sources.contains_key(&file_name) {
+      return PathBuf::from(file_name);
+    }
+    match &self.shader_root {
+      Some(shader_root) => shader_root.join(file_name),
+      None => Path::new("built-in").join(file_name),
+    }
+  }
+
+  fn get_shader_source(&self, shader: &Shader) -> Result<String, String> {
+    if let Some(source) = self.sources.get(&shader.get_file_name()) {
+      return Ok(source.to_owned());
+    }
+
     let location = self.get_shader_location(shader);
-    let source_code = fs::read_to_string(&location).expect(
-      format!("Could not locate shader at location {}", &location).as_str(),
-    );
+    match &self.shader_root {
+      Some(_) => fs::read_to_string(&location).map_err(|error| {
+        format!(
+          "Could not locate shader at location {}: {error}",
+          location.display()
+        )
+      }),
+      None => get_builtin_source(shader).map(String::from).ok_or(format!(
+        "There is no built-in shader {}",
+        location.display()
+      )),
+    }
+  }
+
+  fn load_shader_src(&self, shader: &Shader, id: u32) -> Result<(), String> {
+    let mut source_code = self.get_shader_source(shader)?;
+
+    for (name, include) in &self.includes {
+      source_code =
+        source_code.replace(format!("#include \"{name}\"").as_str(), include);
+    }
 
     let source_code_ptr: *const *const i8 = &(source_code.as_ptr() as *const i8);
+    let source_code_length = source_code.len() as i32;
 
-    unsafe { gl::ShaderSource(id, 1, source_code_ptr, std::ptr::null()) }
+    unsafe { gl::ShaderSource(id, 1, source_code_ptr, &source_code_length) }
+    Ok(())
   }
 
   /**
    * Takes in a shader, loads its source code and
-   * returns its id after compilation
+   * returns its id after compilation, or the compilation log
    */
-  fn init_shader(&self, shader: &Shader) -> u32 {
+  fn init_shader(&self, shader: &Shader) -> Result<u32, String> {
     let shader_id = match shader {
       Shader::VertexShader(ref _name, ref _attributes) => unsafe {
         gl::CreateShader(gl::VERTEX_SHADER)
@@ -145,25 +246,29 @@ impl ShaderProgram {
       },
     };
 
-    self.load_shader_src(shader, shader_id);
+    let compiled = self.load_shader_src(shader, shader_id).and_then(|_| {
+      unsafe { gl::CompileShader(shader_id) }
 
-    unsafe { gl::CompileShader(shader_id) }
+      let error_result =
+        super::gl_error_reader::get_error(GlError::ShaderError(shader_id), 512);
 
-    let error_result =
-      super::gl_error_reader::get_error(GlError::ShaderError(shader_id), 512);
-
-    match error_result {
-      GlErrorResult::Error(error) => {
-        println!(
+      match error_result {
+        GlErrorResult::Error(error) => Err(format!(
           "Shader with id {} could not compile because: {}",
-          self.get_shader_location(shader),
+          self.get_shader_location(shader).display(),
           error
-        );
+        )),
+        GlErrorResult::NoError => Ok(()),
       }
-      _ => {}
-    }
+    });
 
-    return shader_id;
+    match compiled {
+      Ok(()) => Ok(shader_id),
+      Err(error) => {
+        unsafe { gl::DeleteShader(shader_id) };
+        Err(error)
+      }
+    }
   }
 
   pub fn use_program(&self) {
@@ -228,14 +333,18 @@ impl ShaderProgram {
     }
   }
 
-  pub fn load_shaders(&mut self, shaders: Vec<Shader>) {
+  /// Compiles the shaders and links them into the program.
+  /// Returns the `GlErrorResult` log of the first shader that didn't
+  /// compile, or of the linker, or names the first vertex attribute the
+  /// program doesn't have.
+  pub fn load_shaders(&mut self, shaders: Vec<Shader>) -> Result<(), String> {
     for shader in shaders {
       match shader {
         Shader::VertexShader(ref _name, ref _attributes) => {
-          self.shader_map.insert(self.init_shader(&shader), shader);
+          self.shader_map.insert(self.init_shader(&shader)?, shader);
         }
         Shader::FragmentShader(ref _name) => {
-          self.shader_map.insert(self.init_shader(&shader), shader);
+          self.shader_map.insert(self.init_shader(&shader)?, shader);
         }
       }
     }
@@ -247,11 +356,47 @@ impl ShaderProgram {
 
       gl::LinkProgram(self.program_id);
     }
+
+    match super::gl_error_reader::get_error(
+      GlError::ProgramError(self.program_id),
+      512,
+    ) {
+      GlErrorResult::Error(error) => {
+        Err(format!("Shader program could not link because: {error}"))
+      }
+      GlErrorResult::NoError => self.find_attributes(),
+    }
+  }
+
+  /// Fails if an attribute of the vertex shaders is missing from the linked
+  /// program, e.g. because the shader never reads it
+  fn find_attributes(&self) -> Result<(), String> {
+    for shader in self.shader_map.values() {
+      if let Shader::VertexShader(name, attributes) = shader {
+        for attribute in attributes {
+          let attrib_name = get_c_string(attribute.name.to_owned());
+          let location = unsafe {
+            gl::GetAttribLocation(self.program_id, attrib_name.as_ptr())
+          };
+          if location < 0 {
+            return Err(format!(
+              "Vertex shader {name} has no attribute {}",
+              attribute.name
+            ));
+          }
+        }
+      }
+    }
+    Ok(())
   }
 }
 
 impl Drop for ShaderProgram {
   fn drop(&mut self) {
+    // Shells never created a program
+    if self.program_id == 0 {
+      return;
+    }
     unsafe {
       gl::DeleteProgram(self.program_id);
 
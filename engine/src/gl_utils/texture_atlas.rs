@@ -0,0 +1,273 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use crate::{
+  shapes::shape::Shape,
+  sprites::{sprite::Sprite, sprite_sheet::TextureRegion},
+};
+
+use super::{
+  gl_texture::{Texture, TextureOptions},
+  image_data::{ImageData, ImageError},
+};
+
+/// Where an image ended up in a `TextureAtlas`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AtlasEntry {
+  /// Index into the atlas' pages
+  pub page: usize,
+  pub region: TextureRegion,
+}
+
+/// Images packed into a few big textures, so hundreds of small images only
+/// take up a few texture units. Built with a `TextureAtlasBuilder`.
+#[derive(Debug, Clone)]
+pub struct TextureAtlas {
+  pages: Vec<Texture>,
+  entries: HashMap<String, AtlasEntry>,
+}
+
+impl TextureAtlas {
+  pub fn get_pages(&self) -> &Vec<Texture> {
+    &self.pages
+  }
+
+  /// Entry of the image packed under `image_name`
+  pub fn get_entry(&self, image_name: &str) -> Option<AtlasEntry> {
+    self.entries.get(image_name).copied()
+  }
+
+  /// Page texture and region to draw the image packed under `image_name`
+  pub fn get_texture(
+    &self,
+    image_name: &str,
+  ) -> Option<(Texture, TextureRegion)> {
+    let entry = self.get_entry(image_name)?;
+    Some((Texture::from(&self.pages[entry.page]), entry.region))
+  }
+
+  /// Points the sprite at the atlas page its texture was packed into.
+  /// Regions the sprite already had, e.g. a sprite sheet frame, are kept
+  /// relative to the packed image, and later ones can be set relative to it
+  /// with `Sprite::set_image_region`.
+  ///
+  /// Returns false if the sprite's texture isn't part of the atlas.
+  pub fn apply<'a, TShape: Shape + 'a>(
+    &self,
+    sprite: &mut Sprite<'a, TShape>,
+  ) -> bool {
+    let (page, region) = match self.get_texture(&sprite.texture.image_name) {
+      Some(packed) => packed,
+      None => return false,
+    };
+
+    sprite.set_atlas_page(page, region);
+    true
+  }
+}
+
+/// Packs images into `TextureAtlas` pages of at most `page_size` by
+/// `page_size` pixels. Images that are bigger get a page of their own.
+///
+/// Every image is surrounded by `padding` pixels copied from its edges,
+/// so filtering doesn't bleed neighbouring images in.
+pub struct TextureAtlasBuilder {
+  page_size: u32,
+  padding: u32,
+  options: TextureOptions,
+  asset_root: PathBuf,
+  images: Vec<(String, ImageData)>,
+}
+
+/// Spot an image was packed into, in pixels of its page
+struct Placement {
+  page: usize,
+  x: u32,
+  y: u32,
+}
+
+impl TextureAtlasBuilder {
+  pub fn new(page_size: u32) -> TextureAtlasBuilder {
+    TextureAtlasBuilder {
+      page_size,
+      padding: 1,
+      options: TextureOptions::default(),
+      asset_root: PathBuf::from("."),
+      images: vec![],
+    }
+  }
+
+  /// Pixels around every image, 1 by default
+  pub fn set_padding(&mut self, padding: u32) {
+    self.padding = padding;
+  }
+
+  /// Options of the page textures
+  pub fn set_options(&mut self, options: TextureOptions) {
+    self.options = options;
+  }
+
+  /// Directory `add_texture` reads image files from, `.` by default
+  pub fn set_asset_root(&mut self, asset_root: PathBuf) {
+    self.asset_root = asset_root;
+  }
+
+  /// Adds the texture's image under its image name.
+  /// Empty textures and images that were already added are skipped.
+  pub fn add_texture(&mut self, texture: &Texture) -> Result<(), ImageError> {
+    if texture.texture_id < 0 || self.contains(&texture.image_name) {
+      return Ok(());
+    }
+    let image = texture.load_image_data(&self.asset_root)?;
+    self.add_image(&texture.image_name, image);
+    Ok(())
+  }
+
+  /// Adds an image that's already in memory
+  pub fn add_image(&mut self, name: &str, image: ImageData) {
+    self.images.retain(|(existing, _)| existing != name);
+    self.images.push((String::from(name), image));
+  }
+
+  pub fn contains(&self, name: &str) -> bool {
+    self.images.iter().any(|(existing, _)| existing == name)
+  }
+
+  /// Packs the images, tallest first, into rows of pages
+  fn place_images(&self) -> (Vec<Placement>, Vec<(u32, u32)>) {
+    let mut order = (0..self.images.len()).collect::<Vec<usize>>();
+    order.sort_by_key(|index| std::cmp::Reverse(self.images[*index].1.height));
+
+    let mut placements = (0..self.images.len())
+      .map(|_| Placement {
+        page: 0,
+        x: 0,
+        y: 0,
+      })
+      .collect::<Vec<Placement>>();
+    let mut page_sizes: Vec<(u32, u32)> = vec![];
+    let (mut row_x, mut row_y, mut row_height) = (0, 0, 0);
+    let mut current_page: Option<usize> = None;
+
+    for index in order {
+      let image = &self.images[index].1;
+      let width = image.width + self.padding * 2;
+      let height = image.height + self.padding * 2;
+
+      // Too big to share a page
+      if width > self.page_size || height > self.page_size {
+        page_sizes.push((width, height));
+        placements[index] = Placement {
+          page: page_sizes.len() - 1,
+          x: 0,
+          y: 0,
+        };
+        continue;
+      }
+
+      if row_x + width > self.page_size {
+        row_x = 0;
+        row_y += row_height;
+        row_height = 0;
+      }
+
+      let page = match current_page {
+        Some(page) if row_y + height <= self.page_size => page,
+        _ => {
+          page_sizes.push((0, 0));
+          row_x = 0;
+          row_y = 0;
+          row_height = 0;
+          page_sizes.len() - 1
+        }
+      };
+      current_page = Some(page);
+
+      placements[index] = Placement {
+        page,
+        x: row_x,
+        y: row_y,
+      };
+
+      let (page_width, page_height) = page_sizes[page];
+      page_sizes[page] = (
+        page_width.max(row_x + width),
+        page_height.max(row_y + height),
+      );
+
+      row_x += width;
+      row_height = row_height.max(height);
+    }
+
+    (placements, page_sizes)
+  }
+
+  /// Copies the image and its padding into the page at the placement
+  fn blit(&self, page: &mut ImageData, image: &ImageData, x: u32, y: u32) {
+    let padding = self.padding as i64;
+    let width = image.width + self.padding * 2;
+    let height = image.height + self.padding * 2;
+
+    for page_y in y..y + height {
+      for page_x in x..x + width {
+        let source_x = (page_x as i64 - x as i64 - padding)
+          .clamp(0, image.width as i64 - 1) as u32;
+        let source_y = (page_y as i64 - y as i64 - padding)
+          .clamp(0, image.height as i64 - 1) as u32;
+
+        let idx = ((page_y * page.width + page_x) * 4) as usize;
+        page.pixels[idx..idx + 4]
+          .copy_from_slice(&image.get_pixel(source_x, source_y));
+      }
+    }
+  }
+
+  pub fn build(&self) -> TextureAtlas {
+    let (placements, page_sizes) = self.place_images();
+
+    let mut page_images = page_sizes
+      .iter()
+      .map(|(width, height)| {
+        ImageData::new(*width, *height, vec![0; (width * height * 4) as usize])
+      })
+      .collect::<Vec<ImageData>>();
+
+    let mut entries = HashMap::new();
+    for ((name, image), placement) in self.images.iter().zip(placements.iter()) {
+      if image.width == 0 || image.height == 0 {
+        continue;
+      }
+
+      let page = &mut page_images[placement.page];
+      self.blit(page, image, placement.x, placement.y);
+
+      entries.insert(
+        name.to_owned(),
+        AtlasEntry {
+          page: placement.page,
+          region: TextureRegion::from_pixels(
+            placement.x + self.padding,
+            placement.y + self.padding,
+            image.width,
+            image.height,
+            page.width,
+            page.height,
+          ),
+        },
+      );
+    }
+
+    let pages = page_images
+      .into_iter()
+      .enumerate()
+      .map(|(index, image)| {
+        Texture::from_image_data(
+          format!("atlas_page_{index}").as_str(),
+          image,
+          self.options,
+        )
+      })
+      .collect();
+
+    TextureAtlas { pages, entries }
+  }
+}
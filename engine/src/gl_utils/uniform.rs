@@ -31,6 +31,12 @@ impl SettableUniform<Vec<f32>> for UniformFloatVector {
   }
   fn set_uniform(&self, loc: i32) {
     match self.count {
+      1 => {
+        unsafe { gl::Uniform1f(loc, self.values[0]) };
+      }
+      2 => {
+        unsafe { gl::Uniform2f(loc, self.values[0], self.values[1]) };
+      }
       3 => {
         unsafe {
           gl::Uniform3f(
@@ -41,6 +47,17 @@ impl SettableUniform<Vec<f32>> for UniformFloatVector {
           );
         };
       }
+      4 => {
+        unsafe {
+          gl::Uniform4f(
+            loc,
+            self.values[0],
+            self.values[1],
+            self.values[2],
+            self.values[3],
+          );
+        };
+      }
       _ => {
         panic!(
           "Uniform for vector of {} values not implemented",
@@ -43,7 +43,7 @@ impl<T> VertexArrayBuffer<T> {
     self.vertices.len()
   }
 
-  pub fn update_data(&mut self, vertices: &Vec<T>) {
+  pub fn update_data(&mut self, vertices: &[T]) {
     unsafe {
       gl::BindBuffer(gl::ARRAY_BUFFER, self.id);
       gl::BufferData(
@@ -65,6 +65,10 @@ impl<T> VertexArrayBuffer<T> {
 
 impl<T> Drop for VertexArrayBuffer<T> {
   fn drop(&mut self) {
+    // Shells never generated a buffer
+    if self.id == 0 {
+      return;
+    }
     unsafe { gl::DeleteBuffers(1, &self.id) };
   }
 }
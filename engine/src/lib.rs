@@ -1,5 +1,6 @@
 pub extern crate glfw;
 
+mod assets;
 mod bowtie;
 mod components;
 mod general;
@@ -11,30 +12,73 @@ mod window;
 
 pub mod math;
 
+pub use assets::asset_server::{
+  AssetServer, LoadingProgress, DEFAULT_ASSET_GROUP, MAX_ASSET_WORKERS,
+};
+
 pub use bowtie::{
-  component::{ComponentStore, StandardComponent},
+  component::{
+    ComponentContext, ComponentStore, StandardComponent, TypedStore,
+  },
   controller::BowTie,
-  entity::{Component, Entity, Message, StandardEntity},
+  entity::{Component, Entity, StandardEntity},
+  entity_store::EntityId,
+  message::{Message, MessageTarget},
+  query::{EntityQuery, EntityView},
+  time::{Time, DEFAULT_UPDATE_RATE, MAX_FRAME_TIME},
 };
 
 pub use general::{
-  color::Color, color::COLORS, direction::Direction, value::Value,
+  bounds::{Bounds, BoundsBehavior},
+  color::Color,
+  color::COLORS,
+  direction::Direction,
+  value::Value,
 };
 
 pub use gl_utils::{
   gl_error_reader::init_debug_callback,
   gl_texture::{LoadableTexture, Texture, TextureOptions},
-  gl_translation::{TextureFilter, TextureWrap},
+  gl_translation::{DrawingMode, TextureFilter, TextureWrap},
+  image_data::{ImageData, ImageError},
+  texture_atlas::{AtlasEntry, TextureAtlas, TextureAtlasBuilder},
+};
+
+pub use rendering::{
+  backend::{NullBackend, RenderBackend},
+  camera::Camera2D,
+  drawer::{
+    HOT_RELOAD_INTERVAL, VERTEX_COLOR_OFFSET, VERTEX_POSITION_OFFSET, VERTEX_SIZE,
+    VERTEX_TEXTURE_CORDS_OFFSET, VERTEX_TEXTURE_ID_OFFSET,
+    VERTEX_TRANSFORM_OFFSET,
+  },
+  layer::RenderLayer,
+  material::{Material, MaterialAttribute, UniformValue, RESERVED_UNIFORMS},
+  scaling::{ScalingPolicy, Viewport},
+  software_backend::SoftwareBackend,
 };
 
-pub use sprites::{drawable::Drawable, sprite::Sprite};
+pub use sprites::{
+  drawable::Drawable,
+  sprite::Sprite,
+  sprite_sheet::{SpriteSheet, TextureRegion},
+};
 
-pub use shapes::{rectangle::Rectangle, shape::Shape};
+pub use shapes::{
+  circle::Circle, hollow_rectangle::HollowRectangle, line::Line,
+  polygon::Polygon, rectangle::Rectangle, shape::Shape,
+};
 
 pub mod premade_components {
   pub use crate::components::{
-    collide::CollisionComponent, event::EventComponent,
-    gravity::GravityComponent, keyboard_move::KeyboardMoveComponent,
+    animation::{
+      Animation, AnimationComponent, AnimationMode, AnimationState,
+      ANIMATION_FINISHED, PLAY_ANIMATION,
+    },
+    collide::{CollisionComponent, CollisionState},
+    event::EventComponent,
+    gravity::{FallingObject, GravityComponent},
+    keyboard_move::KeyboardMoveComponent,
   };
 }
 
@@ -3,24 +3,22 @@ use std::collections::HashMap;
 pub const PI: f32 = 3.14159265358979;
 const ACCURACY_TAYLOR_SERIES: i32 = 12;
 
-fn factorial_memoized(num: i32, memo_arr: *mut HashMap<i32, i32>) -> i32 {
+fn factorial_memoized(num: i32, memo_arr: &mut HashMap<i32, i32>) -> i32 {
   if num == 1 || num == 0 {
     return 1;
   }
 
-  unsafe {
-    let result: i32 = match (*memo_arr).get(&num) {
-      Some(res) => res.to_owned(),
-      None => {
-        let n_less_1 =
-          factorial_memoized(num.to_owned() - 1, memo_arr) * num.to_owned();
-        (*memo_arr).insert(num.to_owned(), n_less_1);
-        return n_less_1;
-      }
-    };
-
-    return result;
-  }
+  let result: i32 = match memo_arr.get(&num) {
+    Some(res) => res.to_owned(),
+    None => {
+      let n_less_1 =
+        factorial_memoized(num.to_owned() - 1, memo_arr) * num.to_owned();
+      memo_arr.insert(num.to_owned(), n_less_1);
+      return n_less_1;
+    }
+  };
+
+  return result;
 }
 
 pub fn factorial(num: i32) -> i32 {
@@ -0,0 +1,109 @@
+use std::path::Path;
+
+use crate::{
+  general::color::Color,
+  gl_utils::{
+    gl_texture::Texture, gl_translation::DrawingMode, image_data::ImageData,
+  },
+  math::matrix::Matrix,
+};
+
+use super::{material::Material, scaling::Viewport};
+
+/// Texture units backends offer unless they know better.
+/// Every OpenGL 3.3 GPU has at least this many.
+pub const DEFAULT_MAX_TEXTURE_UNITS: usize = 16;
+
+/// Render backend trait
+/// This is what the `Drawer` talks to once it has built the frame's vertex
+/// and element buffers.
+///
+/// Vertices follow the layout described by the `VERTEX_*` constants in the
+/// drawer: position, color, texture coordinates, texture unit and transform.
+pub trait RenderBackend {
+  fn clear(&mut self, color: Color);
+
+  /// Makes the textures available to upcoming draw calls, reading image
+  /// files from under `asset_root`.
+  /// Called every frame, so already loaded textures should be skipped.
+  fn load_textures(&mut self, textures: &[Texture], asset_root: &Path);
+
+  /// Most textures a single draw call can sample from
+  fn get_max_texture_units(&self) -> usize {
+    DEFAULT_MAX_TEXTURE_UNITS
+  }
+
+  /// Binds `textures[unit]` to every texture unit for the upcoming draw call.
+  /// There are never more textures than `get_max_texture_units`.
+  fn bind_textures(&mut self, textures: &[Texture]);
+
+  /// Frees the textures with these ids, nothing uses them anymore.
+  /// Ids that were never loaded should be ignored.
+  fn release_textures(&mut self, texture_ids: &[i32]);
+
+  /// Draws the upcoming draw calls with the material's shaders and
+  /// uniforms, or the built-in shaders for `None`.
+  /// Backends that can't run shaders draw every material like the built-in
+  /// shaders would.
+  fn use_material(&mut self, _material: Option<&Material>) {}
+
+  /// Names and errors of the materials that didn't compile since the last
+  /// call. They are drawn with the built-in shaders instead.
+  fn take_material_errors(&mut self) -> Vec<(String, String)> {
+    vec![]
+  }
+
+  /// Frees whatever the backend made for the materials with these ids,
+  /// nothing uses them anymore
+  fn release_materials(&mut self, _material_ids: &[i32]) {}
+
+  /// Reloads whatever the backend read from files that changed since the
+  /// last call, e.g. shaders. Only called while hot reloading.
+  fn reload_changed_files(&mut self) {}
+
+  /// Sets the row major 4x4 matrix applied after every vertex's transform,
+  /// usually a `Camera2D` projection
+  fn set_projection(&mut self, projection: &Matrix<f32>);
+
+  /// Sets the area of the framebuffer that normalized device coordinates
+  /// are mapped to
+  fn set_viewport(&mut self, viewport: Viewport);
+
+  /// Size of the framebuffer, if the backend owns one
+  fn get_framebuffer_size(&self) -> Option<(u32, u32)> {
+    None
+  }
+
+  fn draw(&mut self, mode: DrawingMode, vertices: &[f32], elements: &[i32]);
+
+  /// Returns the last rendered frame if the backend can read it back
+  fn read_pixels(&self) -> Option<ImageData> {
+    None
+  }
+}
+
+/// Backend that ignores every draw call.
+/// Used by headless BowTies that only care about game logic.
+pub struct NullBackend {}
+
+impl NullBackend {
+  pub fn new() -> NullBackend {
+    NullBackend {}
+  }
+}
+
+impl RenderBackend for NullBackend {
+  fn clear(&mut self, _color: Color) {}
+
+  fn load_textures(&mut self, _textures: &[Texture], _asset_root: &Path) {}
+
+  fn bind_textures(&mut self, _textures: &[Texture]) {}
+
+  fn release_textures(&mut self, _texture_ids: &[i32]) {}
+
+  fn set_projection(&mut self, _projection: &Matrix<f32>) {}
+
+  fn set_viewport(&mut self, _viewport: Viewport) {}
+
+  fn draw(&mut self, _mode: DrawingMode, _vertices: &[f32], _elements: &[i32]) {}
+}
@@ -0,0 +1,93 @@
+use crate::math::matrix::Matrix;
+
+/// 2D camera with an orthographic projection
+///
+/// The camera looks at `(x, y)` and shows `width` by `height` world units,
+/// divided by `zoom`. Rotation is in degrees, counter clockwise.
+///
+/// The default camera shows 2 by 2 units centered on the origin, which is
+/// exactly OpenGL's normalized device coordinates. For pixel units, make the
+/// camera as big as the window, e.g. `Camera2D::new(1000.0, 800.0)`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Camera2D {
+  pub x: f32,
+  pub y: f32,
+  pub width: f32,
+  pub height: f32,
+  pub zoom: f32,
+  pub rotation: f32,
+}
+
+impl Camera2D {
+  pub fn new(width: f32, height: f32) -> Camera2D {
+    Camera2D {
+      x: 0.0,
+      y: 0.0,
+      width,
+      height,
+      zoom: 1.0,
+      rotation: 0.0,
+    }
+  }
+
+  pub fn set_position(&mut self, x: f32, y: f32) {
+    self.x = x;
+    self.y = y;
+  }
+
+  /// Moves the camera by the given amount of world units
+  pub fn move_by(&mut self, x: f32, y: f32) {
+    self.x += x;
+    self.y += y;
+  }
+
+  /// Width and height of the world area that's currently visible
+  pub fn get_visible_size(&self) -> (f32, f32) {
+    (self.width / self.zoom, self.height / self.zoom)
+  }
+
+  /// Row major projection from world units to normalized device coordinates.
+  /// Translates to the camera's position, rotates and then scales.
+  pub fn get_projection(&self) -> Matrix<f32> {
+    let (visible_width, visible_height) = self.get_visible_size();
+    let (sin, cos) = (-self.rotation).to_radians().sin_cos();
+    let (scale_x, scale_y) = (2.0 / visible_width, 2.0 / visible_height);
+
+    Matrix::new(vec![
+      vec![
+        scale_x * cos,
+        -scale_x * sin,
+        0.0,
+        -scale_x * (cos * self.x - sin * self.y),
+      ],
+      vec![
+        scale_y * sin,
+        scale_y * cos,
+        0.0,
+        -scale_y * (sin * self.x + cos * self.y),
+      ],
+      vec![0.0, 0.0, 1.0, 0.0],
+      vec![0.0, 0.0, 0.0, 1.0],
+    ])
+  }
+
+  /// Turns normalized device coordinates, e.g. from the cursor, back into
+  /// world units
+  pub fn to_world(&self, ndc_x: f32, ndc_y: f32) -> (f32, f32) {
+    let (visible_width, visible_height) = self.get_visible_size();
+    let (sin, cos) = self.rotation.to_radians().sin_cos();
+    let (view_x, view_y) =
+      (ndc_x * visible_width / 2.0, ndc_y * visible_height / 2.0);
+
+    (
+      self.x + cos * view_x - sin * view_y,
+      self.y + sin * view_x + cos * view_y,
+    )
+  }
+}
+
+impl Default for Camera2D {
+  fn default() -> Self {
+    Camera2D::new(2.0, 2.0)
+  }
+}
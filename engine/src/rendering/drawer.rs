@@ -1,16 +1,36 @@
-extern crate gl;
-
-use crate::general::color;
-use crate::gl_utils::element_array_buffer::ElementArrayBuffer;
-use crate::gl_utils::gl_texture::{LoadableTexture, Texture};
-use crate::gl_utils::gl_texture_loader::TextureLoader;
-use crate::gl_utils::gl_translation::{DataType, DrawingMode, ToGl, UsageMode};
-use crate::gl_utils::shader_creator::ShaderProgram;
-use crate::gl_utils::vertex_array_buffer::VertexArrayBuffer;
-use crate::shapes::rectangle::Rectangle;
-use crate::sprites::drawable::Drawable;
-use crate::sprites::sprite::Sprite;
-use crate::{Entity, StandardEntity};
+use std::{
+  collections::HashMap,
+  path::{Path, PathBuf},
+  sync::Weak,
+  time::{Duration, Instant},
+};
+
+use crate::bowtie::entity_store::EntityStore;
+use crate::general::{color, file_watcher::FileWatcher};
+use crate::gl_utils::gl_texture::{Texture, TextureHandle};
+use crate::gl_utils::gl_translation::DrawingMode;
+use crate::gl_utils::image_data::{ImageData, ImageError};
+use crate::Entity;
+
+use super::{
+  backend::RenderBackend,
+  camera::Camera2D,
+  material::{Material, MaterialShaders},
+  scaling::Viewport,
+};
+
+/// Floats per vertex: position, color, texture cords, texture id, transform.
+/// Sprites write their texture's id, which the drawer swaps for the texture
+/// unit it's bound to in the vertex' draw call.
+pub const VERTEX_SIZE: usize = 9 + (4 * 4);
+pub const VERTEX_POSITION_OFFSET: usize = 0;
+pub const VERTEX_COLOR_OFFSET: usize = 2;
+pub const VERTEX_TEXTURE_CORDS_OFFSET: usize = 6;
+pub const VERTEX_TEXTURE_ID_OFFSET: usize = 8;
+pub const VERTEX_TRANSFORM_OFFSET: usize = 9;
+
+/// How often hot reloading checks asset files for changes
+pub const HOT_RELOAD_INTERVAL: Duration = Duration::from_millis(500);
 
 #[derive(Clone)]
 pub struct DrawableData {
@@ -18,51 +38,95 @@ pub struct DrawableData {
   pub elements: Vec<i32>,
   pub corner_count: i32,
   pub texture: Texture,
+  pub material: Option<Material>,
 }
 
-pub struct Drawer<'a> {
-  vertex_array_buffer: VertexArrayBuffer<f32>,
-  element_array_buffer: ElementArrayBuffer<i32>,
-  vertices: Vec<f32>,
-  elements: Vec<i32>,
-  texture_loader: TextureLoader,
+/// Vertices and elements drawn in a single draw call, along with the
+/// textures bound to the texture units for it and the material it's drawn
+/// with
+#[derive(Clone, Default)]
+pub struct DrawBatch {
+  pub vertices: Vec<f32>,
+  pub elements: Vec<i32>,
+  pub textures: Vec<Texture>,
+  pub material: Option<Material>,
   elements_count: i32,
-  drawables: Vec<DrawableData>,
-  entities: *const Vec<StandardEntity<'a>>,
 }
 
-impl<'a> Drawer<'a> {
-  pub fn shell() -> Drawer<'a> {
-    Drawer {
-      vertex_array_buffer: VertexArrayBuffer::shell(),
-      element_array_buffer: ElementArrayBuffer::shell(),
-      texture_loader: TextureLoader::new(),
-      vertices: vec![],
-      elements: vec![],
-      elements_count: 0,
-      drawables: vec![],
-      entities: std::ptr::null(),
+impl DrawBatch {
+  /// Whether the batch is drawn the way the material would be
+  fn draws_like(&self, material: &Option<Material>) -> bool {
+    match (&self.material, material) {
+      (Some(batched), Some(material)) => batched.draws_like(material),
+      (None, None) => true,
+      _ => false,
     }
   }
-  pub fn new(usage_mode: UsageMode) -> Drawer<'a> {
+
+  /// Texture unit the texture is bound to in this batch, `None` if it isn't
+  fn get_texture_unit(&self, texture: &Texture) -> Option<usize> {
+    self
+      .textures
+      .iter()
+      .position(|bound| bound.texture_id == texture.texture_id)
+  }
+}
+
+/// Builds the frame's vertex and element buffers out of the entities
+/// and hands them to a `RenderBackend`
+///
+/// Frames that use more textures than the backend has texture units, or
+/// several materials, are split into several draw calls. Textures and
+/// materials are released from the backend once every copy of them is
+/// dropped.
+pub struct Drawer<'a> {
+  backend: Box<dyn RenderBackend + 'a>,
+  batches: Vec<DrawBatch>,
+  /// Directory image files are read from
+  asset_root: PathBuf,
+  /// Every texture handed to the backend, by id
+  textures: HashMap<i32, TrackedTexture>,
+  /// Every material handed to the backend, by id
+  materials: HashMap<i32, Weak<MaterialShaders>>,
+  hot_reload: bool,
+  /// Image files of the textures, from when hot reloading is turned on
+  texture_watcher: Option<FileWatcher>,
+  last_reload: Option<Instant>,
+  /// Image names and errors of the textures that couldn't be reloaded
+  reload_errors: Vec<(String, ImageError)>,
+}
+
+/// Texture the backend may have loaded
+struct TrackedTexture {
+  handle: Weak<TextureHandle>,
+  image_name: String,
+  /// Image file the texture was read from, if any
+  location: Option<PathBuf>,
+}
+
+impl<'a> Drawer<'a> {
+  pub fn new(backend: Box<dyn RenderBackend + 'a>) -> Drawer<'a> {
     Drawer {
-      vertex_array_buffer: VertexArrayBuffer::<f32>::new(
-        DataType::Float32,
-        usage_mode,
-      ),
-      element_array_buffer: ElementArrayBuffer::<i32>::new(
-        DataType::UnsignedInt,
-        usage_mode,
-      ),
-      texture_loader: TextureLoader::new(),
-      vertices: vec![],
-      elements: vec![],
-      elements_count: 0,
-      drawables: vec![],
-      entities: std::ptr::null(),
+      backend,
+      batches: vec![],
+      asset_root: PathBuf::from("."),
+      textures: HashMap::new(),
+      materials: HashMap::new(),
+      hot_reload: false,
+      texture_watcher: None,
+      last_reload: None,
+      reload_errors: vec![],
     }
   }
 
+  pub fn get_asset_root(&self) -> &Path {
+    &self.asset_root
+  }
+
+  pub fn set_asset_root(&mut self, asset_root: PathBuf) {
+    self.asset_root = asset_root;
+  }
+
   /*
    * Loads sprite into this drawer
    * It pushes the sprite's vertices and elements to have it be rendered
@@ -83,119 +147,264 @@ impl<'a> Drawer<'a> {
     }
   }
 
-  pub fn load_drawable_dynamic(
-    &mut self,
-    drawable: DrawableData,
-    program: &ShaderProgram,
-  ) {
-    self.drawables.push(drawable.to_owned());
+  pub fn clear_screen(&mut self, color: color::Color) {
+    self.backend.clear(color);
+  }
 
-    Drawer::load_drawable(
-      &mut self.elements,
-      &mut self.vertices,
-      &self.drawables[self.drawables.len() - 1],
-      self.elements_count,
-    );
+  /// Actually loads the sprite's textures.
+  /// This needs to be done once, but has to be done before the draw call.
+  pub fn prep_textures(&mut self, entities: &EntityStore<'a>) {
+    let textures = entities
+      .iter()
+      .map(|entitiy| entitiy.get_drawable().texture.resolve())
+      .collect::<Vec<Texture>>();
 
-    self.vertex_array_buffer.update_data(&self.vertices);
-    self.element_array_buffer.update_data(&self.elements);
+    self.backend.load_textures(&textures, &self.asset_root);
+    for texture in textures.iter() {
+      self.track_texture(texture);
+    }
   }
 
-  /*
-   * Renders a rectangle as wide and tall as the window to clear it
-   */
-  pub fn clear_screen(&mut self, color: color::Color) {
-    let clear_rect =
-      Sprite::new(Rectangle::new(-1.0, 1.0, 2.0, 2.0, color), Texture::none());
-
-    self
-      .vertex_array_buffer
-      .update_data(&clear_rect.get_vertices());
-    self
-      .element_array_buffer
-      .update_data(&clear_rect.get_elements());
-
-    unsafe {
-      gl::DrawElements(
-        DrawingMode::Triangles.to_gl(),
-        self.elements.len() as i32,
-        self.element_array_buffer.data_type.to_gl(),
-        0 as *const gl::types::GLvoid,
+  /// Remembers the texture so it can be released once it's dropped
+  fn track_texture(&mut self, texture: &Texture) {
+    if self.textures.contains_key(&texture.texture_id) {
+      return;
+    }
+    if let Some(handle) = texture.downgrade() {
+      let location = if texture.is_from_file() {
+        Some(texture.get_image_location(&self.asset_root))
+      } else {
+        None
+      };
+      if let (Some(watcher), Some(location)) =
+        (self.texture_watcher.as_mut(), location.as_ref())
+      {
+        watcher.watch(location);
+      }
+      self.textures.insert(
+        texture.texture_id,
+        TrackedTexture {
+          handle,
+          image_name: texture.image_name.to_owned(),
+          location,
+        },
       );
     }
   }
 
-  pub fn prep_data(&mut self, program: &ShaderProgram) {
-    self.load_all(program);
+  /// Has the backend release every texture that isn't used anymore.
+  /// Texture units are handed out per draw call, so released textures stop
+  /// taking one up right away.
+  fn release_dropped_textures(&mut self) {
+    let dropped = self
+      .textures
+      .iter()
+      .filter(|(_, tracked)| tracked.handle.strong_count() == 0)
+      .map(|(texture_id, _)| *texture_id)
+      .collect::<Vec<i32>>();
+
+    if dropped.is_empty() {
+      return;
+    }
+    for texture_id in dropped.iter() {
+      self.textures.remove(texture_id);
+    }
+    self.backend.release_textures(&dropped);
   }
 
-  /// Actually loads the sprite's textures.
-  /// This needs to be done once, but has to be done before the draw call.
-  pub fn prep_textures(&mut self, program: &ShaderProgram) {
-    let entities = unsafe { self.entities.as_ref().unwrap() };
-    let textures = entities
+  /// Has the backend release every material that isn't used anymore
+  fn release_dropped_materials(&mut self) {
+    let dropped = self
+      .materials
       .iter()
-      .map(|entitiy| entitiy.get_drawable().texture.to_owned())
-      .collect::<Vec<Texture>>();
+      .filter(|(_, shaders)| shaders.strong_count() == 0)
+      .map(|(material_id, _)| *material_id)
+      .collect::<Vec<i32>>();
 
-    self.texture_loader.load_textures(textures, program);
+    if dropped.is_empty() {
+      return;
+    }
+    for material_id in dropped.iter() {
+      self.materials.remove(material_id);
+    }
+    self.backend.release_materials(&dropped);
+  }
 
-    unsafe {
-      gl::Enable(gl::BLEND);
-      gl::BlendEquation(gl::FUNC_ADD);
-      gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+  pub fn is_hot_reloading(&self) -> bool {
+    self.hot_reload
+  }
+
+  /// Checks asset files for changes every `HOT_RELOAD_INTERVAL` while
+  /// drawing, see `reload_changed_assets`
+  pub fn set_hot_reload(&mut self, hot_reload: bool) {
+    self.hot_reload = hot_reload;
+    if hot_reload && self.texture_watcher.is_none() {
+      self.texture_watcher = Some(FileWatcher::new());
     }
   }
 
-  pub fn set_entities_array(
-    &mut self,
-    entities: *const Vec<StandardEntity<'a>>,
-  ) {
-    self.entities = entities;
+  /// Reads the image files of textures that changed since they were loaded
+  /// or last reloaded again, and has the backend reload its own files,
+  /// e.g. shaders. Files of textures loaded before hot reloading was turned
+  /// on are only checked from the first call on.
+  pub fn reload_changed_assets(&mut self) {
+    self.last_reload = Some(Instant::now());
+    let watcher = self.texture_watcher.get_or_insert_with(FileWatcher::new);
+
+    for tracked in self.textures.values() {
+      if let Some(location) = &tracked.location {
+        watcher.watch(location);
+      }
+    }
+    let changed = watcher.poll();
+
+    let mut reloaded = vec![];
+    for (texture_id, tracked) in self.textures.iter() {
+      let (location, handle) =
+        match (&tracked.location, tracked.handle.upgrade()) {
+          (Some(location), Some(handle)) if changed.contains(location) => {
+            (location, handle)
+          }
+          _ => continue,
+        };
+
+      match handle.reload_image(location) {
+        Ok(()) => reloaded.push(*texture_id),
+        Err(error) => {
+          self
+            .reload_errors
+            .push((tracked.image_name.to_owned(), error));
+        }
+      }
+    }
+
+    // The backend loads them again on the next draw
+    if !reloaded.is_empty() {
+      self.backend.release_textures(&reloaded);
+    }
+    self.backend.reload_changed_files();
+  }
+
+  /// Image names and errors of the textures whose changed files couldn't be
+  /// read since the last call. They keep their previous pixels.
+  pub fn take_reload_errors(&mut self) -> Vec<(String, ImageError)> {
+    std::mem::take(&mut self.reload_errors)
   }
 
-  pub fn load_all(&mut self, program: &ShaderProgram) {
-    let size = self.vertices.len();
-    self.vertices.clear();
-    self.elements.clear();
-    self.elements_count = 0;
+  /// Names and errors of the materials the backend couldn't compile since
+  /// the last call, see `RenderBackend::take_material_errors`
+  pub fn take_material_errors(&mut self) -> Vec<(String, String)> {
+    self.backend.take_material_errors()
+  }
 
-    self.vertices.reserve(size);
+  /// Builds the frame's batches, with every entity blended `alpha` of the
+  /// way from its previous position to its current one.
+  /// Entities are sorted by z-index, so higher ones are drawn on top.
+  /// Neighbouring entities with the same material share draw calls.
+  pub fn load_all(&mut self, entities: &EntityStore<'a>, alpha: f32) {
+    let max_texture_units = self.backend.get_max_texture_units().max(1);
+    self.batches.clear();
+    let mut batch = DrawBatch::default();
 
-    let entities = unsafe { self.entities.as_ref().unwrap() };
-    let len = entities.len();
+    let mut sorted_entities = entities.iter().collect::<Vec<_>>();
+    sorted_entities
+      .sort_by_key(|entity| (entity.get_z_index(), entity.get_load_sequence()));
 
-    for i in 0..len {
-      let entity = &entities[i];
+    for entity in sorted_entities {
+      let mut drawable = entity.get_interpolated_drawable(alpha);
+      let texture = drawable.texture.resolve();
+
+      if !batch.draws_like(&drawable.material) {
+        if !batch.vertices.is_empty() {
+          self.batches.push(std::mem::take(&mut batch));
+        }
+        if let Some(material) = &drawable.material {
+          self
+            .materials
+            .entry(material.material_id)
+            .or_insert_with(|| material.downgrade());
+        }
+        batch.material = drawable.material.to_owned();
+      }
+
+      let texture_unit = if texture.texture_id < 0 {
+        -1
+      } else {
+        let unit = match batch.get_texture_unit(&texture) {
+          Some(unit) => unit,
+          None => {
+            // Out of texture units, the rest goes into the next draw call
+            if batch.textures.len() == max_texture_units {
+              let material = batch.material.to_owned();
+              self.batches.push(std::mem::take(&mut batch));
+              batch.material = material;
+            }
+            self.track_texture(&texture);
+            batch.textures.push(texture);
+            batch.textures.len() - 1
+          }
+        };
+        unit as i32
+      };
+
+      for vertex in drawable.vertices.chunks_mut(VERTEX_SIZE) {
+        vertex[VERTEX_TEXTURE_ID_OFFSET] = texture_unit as f32;
+      }
 
-      let drawable = entity.get_drawable();
-      self
-        .texture_loader
-        .load_texture(drawable.to_owned().texture, program);
       Drawer::load_drawable(
-        &mut self.elements,
-        &mut self.vertices,
+        &mut batch.elements,
+        &mut batch.vertices,
         &drawable,
-        self.elements_count,
+        batch.elements_count,
       );
-      self.elements_count += drawable.corner_count
+      batch.elements_count += drawable.corner_count;
     }
 
-    self.vertex_array_buffer.update_data(&self.vertices);
-    self.element_array_buffer.update_data(&self.elements);
+    self.batches.push(batch);
   }
 
-  /// Renders the dynamically loaded sprites
-  pub fn draw(&mut self, mode: DrawingMode, program: &ShaderProgram) {
-    self.load_all(program);
+  /// Uses the camera's projection for the upcoming draw calls
+  pub fn set_camera(&mut self, camera: &Camera2D) {
+    self.backend.set_projection(&camera.get_projection());
+  }
 
-    unsafe {
-      gl::DrawElements(
-        mode.to_gl(),
-        self.elements.len() as i32,
-        self.element_array_buffer.data_type.to_gl(),
-        0 as *const gl::types::GLvoid,
-      );
+  pub fn set_viewport(&mut self, viewport: Viewport) {
+    self.backend.set_viewport(viewport);
+  }
+
+  pub fn get_framebuffer_size(&self) -> Option<(u32, u32)> {
+    self.backend.get_framebuffer_size()
+  }
+
+  /// Renders the entities
+  pub fn draw(
+    &mut self,
+    mode: DrawingMode,
+    entities: &EntityStore<'a>,
+    alpha: f32,
+  ) {
+    let reload_due = self.last_reload.map_or(true, |last_reload| {
+      last_reload.elapsed() >= HOT_RELOAD_INTERVAL
+    });
+    if self.hot_reload && reload_due {
+      self.reload_changed_assets();
     }
+
+    self.load_all(entities, alpha);
+    self.release_dropped_textures();
+    self.release_dropped_materials();
+    for batch in self.batches.iter() {
+      self
+        .backend
+        .load_textures(&batch.textures, &self.asset_root);
+      self.backend.use_material(batch.material.as_ref());
+      self.backend.bind_textures(&batch.textures);
+      self.backend.draw(mode, &batch.vertices, &batch.elements);
+    }
+  }
+
+  /// Reads the last frame back, if the backend supports it
+  pub fn read_pixels(&self) -> Option<ImageData> {
+    self.backend.read_pixels()
   }
 }
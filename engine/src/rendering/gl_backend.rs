@@ -0,0 +1,420 @@
+extern crate gl;
+
+use std::{collections::HashMap, path::Path};
+
+use crate::{
+  general::{color::Color, file_watcher::FileWatcher},
+  gl_utils::{
+    element_array_buffer::ElementArrayBuffer,
+    gl_texture::Texture,
+    gl_texture_loader::TextureLoader,
+    gl_translation::{DataType, DrawingMode, ToGl, UsageMode},
+    shader_creator::{
+      Shader, ShaderProgram, VertexShaderAttribute, VertexShaderAttributeType,
+    },
+    uniform::{UniformFloatVector, UniformInteger, UniformMatrixFloat},
+    vertex_array_buffer::VertexArrayBuffer,
+  },
+  math::matrix::{IdentityMatrix, Matrix},
+};
+
+use super::{
+  backend::RenderBackend,
+  drawer::VERTEX_SIZE,
+  material::{Material, MaterialAttribute, UniformValue},
+  scaling::Viewport,
+};
+
+/// Number of texture units fragment shaders can sample from on this GPU
+fn get_max_texture_units() -> usize {
+  let mut texture_units = 0;
+  unsafe {
+    gl::GetIntegerv(gl::MAX_TEXTURE_IMAGE_UNITS, &mut texture_units);
+  }
+  texture_units.max(1) as usize
+}
+
+/// GLSL declaring a sampler for every texture unit, and `sample_texture` to
+/// pick one by unit. GLSL 3.30 only indexes sampler arrays with constants,
+/// so it switches over every unit.
+fn get_texture_samplers_source(texture_units: usize) -> String {
+  let mut cases = String::new();
+  for unit in 0..texture_units {
+    cases.push_str(
+      format!(
+        "    case {unit}: return texture(tex_samplers[{unit}], tex_cords);\n"
+      )
+      .as_str(),
+    );
+  }
+
+  format!(
+    "uniform sampler2D tex_samplers[{texture_units}];
+
+vec4 sample_texture(int unit, vec2 tex_cords) {{
+  switch(unit) {{
+{cases}  }}
+  return vec4(1.0, 1.0, 1.0, 1.0);
+}}
+"
+  )
+}
+
+/// The shaders' sources in the crate, which hot reloading watches
+const SHADER_SOURCE_ROOT: &str =
+  concat!(env!("CARGO_MANIFEST_DIR"), "/shaders/");
+
+/// Vertex shader attribute reading the material attribute's floats out of
+/// every vertex
+fn to_shader_attribute(attribute: &MaterialAttribute) -> VertexShaderAttribute {
+  let (size, attrib_type) = if attribute.is_matrix() {
+    (4, VertexShaderAttributeType::Matrix4)
+  } else {
+    (attribute.size as i32, VertexShaderAttributeType::Vector)
+  };
+
+  VertexShaderAttribute::new(
+    attribute.name.to_owned(),
+    DataType::Float32,
+    size,
+    VERTEX_SIZE as i32,
+    true,
+    attribute.offset as i32,
+    attrib_type,
+  )
+}
+
+/// Initiates a shader program with pre-defined vertex attributes, reading
+/// the shaders from `shader_root` if there is one
+fn get_program(
+  texture_units: usize,
+  shader_root: Option<&Path>,
+) -> Result<ShaderProgram, String> {
+  let mut program = ShaderProgram::new();
+  if let Some(shader_root) = shader_root {
+    program.set_shader_root(shader_root);
+  }
+  program.set_include(
+    "texture_samplers",
+    get_texture_samplers_source(texture_units),
+  );
+  program.load_shaders(vec![
+    Shader::VertexShader(
+      String::from("main"),
+      MaterialAttribute::default_layout()
+        .iter()
+        .map(to_shader_attribute)
+        .collect(),
+    ),
+    Shader::FragmentShader(String::from("main")),
+  ])?;
+  Ok(program)
+}
+
+/// Compiles the material's shaders into a program with its attributes
+fn get_material_program(
+  texture_units: usize,
+  material: &Material,
+) -> Result<ShaderProgram, String> {
+  material.validate()?;
+
+  let name = format!("material_{}", material.material_id);
+  let vertex_shader = Shader::VertexShader(
+    name.to_owned(),
+    material
+      .get_attributes()
+      .iter()
+      .map(to_shader_attribute)
+      .collect(),
+  );
+  let fragment_shader = Shader::FragmentShader(name);
+
+  let mut program = ShaderProgram::new();
+  program.set_include(
+    "texture_samplers",
+    get_texture_samplers_source(texture_units),
+  );
+  program.set_source(
+    vertex_shader.get_file_name().as_str(),
+    String::from(material.get_vertex_source()),
+  );
+  program.set_source(
+    fragment_shader.get_file_name().as_str(),
+    String::from(material.get_fragment_source()),
+  );
+  program.load_shaders(vec![vertex_shader, fragment_shader])?;
+  Ok(program)
+}
+
+/// Sets a material uniform on the current program, skipping uniforms the
+/// program doesn't have
+fn set_material_uniform(
+  program: &ShaderProgram,
+  name: &str,
+  value: &UniformValue,
+) {
+  match value {
+    UniformValue::Int(value) => {
+      program.set_optional_uniform(&UniformInteger::new(name, *value))
+    }
+    UniformValue::Float(value) => program
+      .set_optional_uniform(&UniformFloatVector::new(name, 1, vec![*value])),
+    UniformValue::Vec2(values) => program
+      .set_optional_uniform(&UniformFloatVector::new(name, 2, values.to_vec())),
+    UniformValue::Vec3(values) => program
+      .set_optional_uniform(&UniformFloatVector::new(name, 3, values.to_vec())),
+    UniformValue::Vec4(values) => program
+      .set_optional_uniform(&UniformFloatVector::new(name, 4, values.to_vec())),
+    UniformValue::Matrix4(values) => {
+      program.set_optional_uniform(&UniformMatrixFloat::new(
+        name,
+        Matrix::new(values.chunks(4).map(|row| row.to_vec()).collect()),
+      ))
+    }
+  };
+}
+
+/// OpenGL backend
+/// Needs a current gl context, so it can only be created after the window.
+pub struct GlBackend {
+  vertex_array_buffer: VertexArrayBuffer<f32>,
+  element_array_buffer: ElementArrayBuffer<i32>,
+  texture_loader: TextureLoader,
+  shading_program: ShaderProgram,
+  /// Programs of the materials by id, `None` for materials that didn't
+  /// compile, which are drawn with the shading program instead
+  material_programs: HashMap<i32, Option<ShaderProgram>>,
+  /// Material of the program in use, `None` for the shading program
+  active_material: Option<i32>,
+  /// Names and errors of the materials that didn't compile
+  material_errors: Vec<(String, String)>,
+  projection: Matrix<f32>,
+  max_texture_units: usize,
+  /// Shader sources, from the first hot reload on
+  shader_watcher: Option<FileWatcher>,
+}
+
+impl GlBackend {
+  pub fn new(usage_mode: UsageMode) -> GlBackend {
+    let max_texture_units = get_max_texture_units();
+    let mut backend = GlBackend {
+      vertex_array_buffer: VertexArrayBuffer::<f32>::new(
+        DataType::Float32,
+        usage_mode,
+      ),
+      element_array_buffer: ElementArrayBuffer::<i32>::new(
+        DataType::UnsignedInt,
+        usage_mode,
+      ),
+      texture_loader: TextureLoader::new(),
+      shading_program: get_program(max_texture_units, None)
+        .unwrap_or_else(|error| panic!("{error}")),
+      material_programs: HashMap::new(),
+      active_material: None,
+      material_errors: vec![],
+      projection: Matrix::generate_identity(4),
+      max_texture_units,
+      shader_watcher: None,
+    };
+
+    // Attribute pointers are bound to the current array buffer
+    backend.vertex_array_buffer.update_data(&[]);
+    backend.use_program();
+
+    unsafe {
+      gl::Enable(gl::BLEND);
+      gl::BlendEquation(gl::FUNC_ADD);
+      gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+    }
+
+    backend
+  }
+
+  /// Switches to the shading program and points its samplers at their units
+  fn use_program(&mut self) {
+    self.shading_program.use_program();
+    self.active_material = None;
+
+    // Every sampler reads from the unit matching its index
+    for unit in 0..self.max_texture_units {
+      self.shading_program.set_uniform(&UniformInteger::new(
+        format!("tex_samplers[{unit}]").as_str(),
+        unit as i32,
+      ));
+    }
+    self.set_projection_uniform();
+  }
+
+  /// Program of the material, compiling it the first time it's used.
+  /// `None` if it doesn't compile, the error is kept for
+  /// `take_material_errors`.
+  fn load_material_program(
+    &mut self,
+    material: &Material,
+  ) -> Option<&ShaderProgram> {
+    let max_texture_units = self.max_texture_units;
+    let material_errors = &mut self.material_errors;
+    self
+      .material_programs
+      .entry(material.material_id)
+      .or_insert_with(|| {
+        match get_material_program(max_texture_units, material) {
+          Ok(program) => Some(program),
+          Err(error) => {
+            material_errors.push((material.name.to_owned(), error));
+            None
+          }
+        }
+      })
+      .as_ref()
+  }
+
+  /// Sets the projection on the program in use
+  fn set_projection_uniform(&self) {
+    let projection =
+      UniformMatrixFloat::new("projection", self.projection.to_owned());
+    match self.active_material {
+      Some(material_id) => {
+        if let Some(Some(program)) = self.material_programs.get(&material_id) {
+          program.set_optional_uniform(&projection);
+        }
+      }
+      None => self.shading_program.set_uniform(&projection),
+    }
+  }
+}
+
+impl RenderBackend for GlBackend {
+  fn clear(&mut self, color: Color) {
+    unsafe {
+      gl::ClearColor(color.r, color.g, color.b, color.a);
+      gl::Clear(gl::COLOR_BUFFER_BIT);
+    }
+  }
+
+  fn load_textures(&mut self, textures: &[Texture], asset_root: &Path) {
+    self.texture_loader.load_textures(textures, asset_root);
+  }
+
+  fn get_max_texture_units(&self) -> usize {
+    self.max_texture_units
+  }
+
+  fn bind_textures(&mut self, textures: &[Texture]) {
+    self.texture_loader.bind_textures(textures);
+  }
+
+  fn release_textures(&mut self, texture_ids: &[i32]) {
+    self.texture_loader.release_textures(texture_ids);
+  }
+
+  /// Switches to the material's program and sets its uniforms. Materials
+  /// that don't compile are drawn with the built-in shaders.
+  fn use_material(&mut self, material: Option<&Material>) {
+    let material = match material {
+      Some(material) => material,
+      None => {
+        if self.active_material.is_some() {
+          self.use_program();
+        }
+        return;
+      }
+    };
+
+    let max_texture_units = self.max_texture_units;
+    let program = match self.load_material_program(material) {
+      Some(program) => program,
+      None => {
+        if self.active_material.is_some() {
+          self.use_program();
+        }
+        return;
+      }
+    };
+
+    program.use_program();
+    for unit in 0..max_texture_units {
+      program.set_optional_uniform(&UniformInteger::new(
+        format!("tex_samplers[{unit}]").as_str(),
+        unit as i32,
+      ));
+    }
+    for (name, value) in material.get_uniforms() {
+      set_material_uniform(program, name, value);
+    }
+
+    self.active_material = Some(material.material_id);
+    self.set_projection_uniform();
+  }
+
+  fn take_material_errors(&mut self) -> Vec<(String, String)> {
+    std::mem::take(&mut self.material_errors)
+  }
+
+  fn release_materials(&mut self, material_ids: &[i32]) {
+    for material_id in material_ids {
+      if self.active_material == Some(*material_id) {
+        self.use_program();
+      }
+      self.material_programs.remove(material_id);
+    }
+  }
+
+  /// Recompiles the shaders from their sources in the crate once one
+  /// changes. The current program is kept if the new one doesn't build.
+  fn reload_changed_files(&mut self) {
+    let shader_root = Path::new(SHADER_SOURCE_ROOT);
+    if self.shader_watcher.is_none() {
+      let mut watcher = FileWatcher::new();
+      for shader in self.shading_program.get_shaders() {
+        watcher.watch(&shader_root.join(shader.get_file_name()));
+      }
+      self.shader_watcher = Some(watcher);
+    }
+
+    let changed = self.shader_watcher.as_mut().unwrap().poll();
+    if changed.is_empty() {
+      return;
+    }
+
+    match get_program(self.max_texture_units, Some(shader_root)) {
+      Ok(program) => {
+        self.shading_program = program;
+        self.use_program();
+      }
+      Err(error) => {
+        println!("Keeping the current shader program: {error}");
+      }
+    }
+  }
+
+  fn set_projection(&mut self, projection: &Matrix<f32>) {
+    self.projection = projection.to_owned();
+    self.set_projection_uniform();
+  }
+
+  fn set_viewport(&mut self, viewport: Viewport) {
+    unsafe {
+      gl::Viewport(
+        viewport.x,
+        viewport.y,
+        viewport.width as i32,
+        viewport.height as i32,
+      );
+    }
+  }
+
+  fn draw(&mut self, mode: DrawingMode, vertices: &[f32], elements: &[i32]) {
+    self.vertex_array_buffer.update_data(vertices);
+    self.element_array_buffer.update_data(elements);
+
+    unsafe {
+      gl::DrawElements(
+        mode.to_gl(),
+        elements.len() as i32,
+        self.element_array_buffer.data_type.to_gl(),
+        0 as *const gl::types::GLvoid,
+      );
+    }
+  }
+}
@@ -0,0 +1,18 @@
+/// Named z-indices for the usual layers of a 2D game.
+/// Entities on higher layers are drawn on top of lower ones.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RenderLayer {
+  Background,
+  World,
+  Ui,
+}
+
+impl From<RenderLayer> for i32 {
+  fn from(layer: RenderLayer) -> Self {
+    match layer {
+      RenderLayer::Background => -100,
+      RenderLayer::World => 0,
+      RenderLayer::Ui => 100,
+    }
+  }
+}
@@ -0,0 +1,200 @@
+use std::{
+  collections::BTreeMap,
+  sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc, Weak,
+  },
+};
+
+use super::drawer::{
+  VERTEX_COLOR_OFFSET, VERTEX_POSITION_OFFSET, VERTEX_SIZE,
+  VERTEX_TEXTURE_CORDS_OFFSET, VERTEX_TEXTURE_ID_OFFSET,
+  VERTEX_TRANSFORM_OFFSET,
+};
+
+/// Hands out the ids that tell materials apart, like texture ids
+static MATERIAL_COUNT: AtomicU32 = AtomicU32::new(0);
+
+/// Uniforms the engine sets itself, materials can't override them
+pub const RESERVED_UNIFORMS: [&str; 2] = ["projection", "tex_samplers"];
+
+/// Whether the uniform is one of `RESERVED_UNIFORMS` or an element of one
+fn is_reserved_uniform(name: &str) -> bool {
+  RESERVED_UNIFORMS.iter().any(|reserved| {
+    name
+      .strip_prefix(reserved)
+      .is_some_and(|rest| rest.is_empty() || rest.starts_with('['))
+  })
+}
+
+/// Value of a material uniform, matching the GLSL type it's declared with
+#[derive(Debug, Clone, PartialEq)]
+pub enum UniformValue {
+  Int(i32),
+  Float(f32),
+  Vec2([f32; 2]),
+  Vec3([f32; 3]),
+  Vec4([f32; 4]),
+  /// Row major 4x4 matrix, like the projection
+  Matrix4([f32; 16]),
+}
+
+/// Vertex attribute a material's vertex shader reads, as `size` floats
+/// starting `offset` floats into every vertex.
+///
+/// Vertices always follow the layout of the `VERTEX_*` constants, the
+/// attributes only declare which parts of it the shader reads under which
+/// names. A size of 16 is read as a `mat4`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaterialAttribute {
+  pub name: String,
+  pub offset: usize,
+  pub size: usize,
+}
+
+impl MaterialAttribute {
+  pub fn new(name: &str, offset: usize, size: usize) -> MaterialAttribute {
+    MaterialAttribute {
+      name: String::from(name),
+      offset,
+      size,
+    }
+  }
+
+  /// Layout of the built-in vertex shader:
+  /// `vec2 position`, `vec4 targetColor`, `vec2 tex_cords_in`,
+  /// `float tex_id` and `mat4 trans`
+  pub fn default_layout() -> Vec<MaterialAttribute> {
+    vec![
+      MaterialAttribute::new("position", VERTEX_POSITION_OFFSET, 2),
+      MaterialAttribute::new("targetColor", VERTEX_COLOR_OFFSET, 4),
+      MaterialAttribute::new("tex_cords_in", VERTEX_TEXTURE_CORDS_OFFSET, 2),
+      MaterialAttribute::new("tex_id", VERTEX_TEXTURE_ID_OFFSET, 1),
+      MaterialAttribute::new("trans", VERTEX_TRANSFORM_OFFSET, 16),
+    ]
+  }
+
+  pub fn is_matrix(&self) -> bool {
+    self.size == 16
+  }
+
+  /// Fails if the attribute isn't a float, vector or `mat4`, or reaches
+  /// past the end of a vertex
+  pub fn validate(&self) -> Result<(), String> {
+    if !matches!(self.size, 1..=4 | 16) {
+      return Err(format!(
+        "Attribute {} has {} floats, it needs 1 to 4, or 16 for a mat4",
+        self.name, self.size
+      ));
+    }
+    if self.offset + self.size > VERTEX_SIZE {
+      return Err(format!(
+        "Attribute {} reaches past the {VERTEX_SIZE} floats of a vertex",
+        self.name
+      ));
+    }
+    Ok(())
+  }
+}
+
+/// What copies of a material share
+#[derive(Debug)]
+pub(crate) struct MaterialShaders {
+  vertex_source: String,
+  fragment_source: String,
+  attributes: Vec<MaterialAttribute>,
+}
+
+/// Material
+///
+/// GLSL vertex and fragment shaders sprites are drawn with instead of the
+/// built-in ones, e.g. for dissolve, outline or palette swap effects.
+/// Shaders can `#include "texture_samplers"` to get
+/// `sample_texture(int unit, vec2 tex_cords)` for the vertex' `tex_id`,
+/// and should declare `uniform mat4 projection` to follow the camera.
+///
+/// Copies of a material share its compiled shaders but each have their own
+/// uniforms. The `Drawer` keeps the draw order, sprites drawn one after
+/// the other with the same material and uniform values share draw calls.
+///
+/// Backends that can't run shaders, like the `SoftwareBackend`, draw every
+/// material like the built-in shaders would.
+#[derive(Debug, Clone)]
+pub struct Material {
+  pub material_id: i32,
+  pub name: String,
+  shaders: Arc<MaterialShaders>,
+  uniforms: BTreeMap<String, UniformValue>,
+}
+
+impl Material {
+  /// Material reading the vertex attributes in `attributes`, see
+  /// `MaterialAttribute::default_layout`
+  pub fn new(
+    name: &str,
+    vertex_source: &str,
+    fragment_source: &str,
+    attributes: Vec<MaterialAttribute>,
+  ) -> Material {
+    let material_id = MATERIAL_COUNT.fetch_add(1, Ordering::SeqCst);
+
+    Material {
+      material_id: material_id as i32,
+      name: String::from(name),
+      shaders: Arc::new(MaterialShaders {
+        vertex_source: String::from(vertex_source),
+        fragment_source: String::from(fragment_source),
+        attributes,
+      }),
+      uniforms: BTreeMap::new(),
+    }
+  }
+
+  pub fn get_vertex_source(&self) -> &str {
+    &self.shaders.vertex_source
+  }
+
+  pub fn get_fragment_source(&self) -> &str {
+    &self.shaders.fragment_source
+  }
+
+  pub fn get_attributes(&self) -> &Vec<MaterialAttribute> {
+    &self.shaders.attributes
+  }
+
+  /// Sets the uniform for every draw call of this copy of the material.
+  /// Panics for the uniforms in `RESERVED_UNIFORMS` and their elements,
+  /// e.g. `tex_samplers[0]`.
+  pub fn set_uniform(&mut self, name: &str, value: UniformValue) {
+    assert!(
+      !is_reserved_uniform(name),
+      "Uniform {name} is set by the engine"
+    );
+    self.uniforms.insert(String::from(name), value);
+  }
+
+  pub fn get_uniform(&self, name: &str) -> Option<&UniformValue> {
+    self.uniforms.get(name)
+  }
+
+  pub fn get_uniforms(&self) -> &BTreeMap<String, UniformValue> {
+    &self.uniforms
+  }
+
+  /// Whether both draw the same, so they can share a draw call
+  pub fn draws_like(&self, other: &Material) -> bool {
+    self.material_id == other.material_id && self.uniforms == other.uniforms
+  }
+
+  /// Fails if an attribute is invalid, see `MaterialAttribute::validate`
+  pub fn validate(&self) -> Result<(), String> {
+    self
+      .get_attributes()
+      .iter()
+      .try_for_each(|attribute| attribute.validate())
+  }
+
+  pub(crate) fn downgrade(&self) -> Weak<MaterialShaders> {
+    Arc::downgrade(&self.shaders)
+  }
+}
@@ -1 +1,10 @@
+pub mod backend;
+pub mod camera;
 pub mod drawer;
+pub mod gl_backend;
+pub mod layer;
+pub mod material;
+pub mod scaling;
+pub mod software_backend;
+
+mod test;
@@ -0,0 +1,116 @@
+use super::camera::Camera2D;
+
+/// Area of the framebuffer that gets drawn to, in pixels.
+/// Like OpenGL's viewport, `(x, y)` is its bottom left corner.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Viewport {
+  pub x: i32,
+  pub y: i32,
+  pub width: u32,
+  pub height: u32,
+}
+
+impl Viewport {
+  pub fn new(x: i32, y: i32, width: u32, height: u32) -> Viewport {
+    Viewport {
+      x,
+      y,
+      width,
+      height,
+    }
+  }
+
+  /// Viewport covering the whole framebuffer
+  pub fn full(width: u32, height: u32) -> Viewport {
+    Viewport::new(0, 0, width, height)
+  }
+}
+
+/// How the camera's view is fit into the framebuffer when their aspect
+/// ratios differ, e.g. after the window was resized
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScalingPolicy {
+  /// Fills the whole framebuffer, squashing or stretching the view
+  Stretch,
+  /// Keeps the camera's aspect ratio and fills the rest with bars
+  Letterbox,
+  /// Fills the whole framebuffer and keeps the camera's height, showing
+  /// more or less of the world to the sides
+  FixedHeight,
+  /// Treats the camera's size as a pixel resolution and scales it up by the
+  /// biggest whole number that fits, for crisp pixel art
+  IntegerScale,
+}
+
+impl ScalingPolicy {
+  /// Camera to draw with and the viewport to draw into, for a framebuffer
+  /// of the given size
+  pub fn apply(
+    &self,
+    camera: &Camera2D,
+    framebuffer_width: u32,
+    framebuffer_height: u32,
+  ) -> (Camera2D, Viewport) {
+    let full = Viewport::full(framebuffer_width, framebuffer_height);
+    if framebuffer_width == 0 || framebuffer_height == 0 {
+      return (camera.to_owned(), full);
+    }
+    // Nothing to keep the aspect ratio or pixel size of
+    if camera.width <= 0.0 || camera.height <= 0.0 {
+      return (camera.to_owned(), full);
+    }
+
+    let (fb_width, fb_height) =
+      (framebuffer_width as f32, framebuffer_height as f32);
+
+    match self {
+      ScalingPolicy::Stretch => (camera.to_owned(), full),
+      ScalingPolicy::Letterbox => {
+        let aspect_ratio = camera.width / camera.height;
+        let (width, height) = if fb_width / fb_height > aspect_ratio {
+          (fb_height * aspect_ratio, fb_height)
+        } else {
+          (fb_width, fb_width / aspect_ratio)
+        };
+        (
+          camera.to_owned(),
+          ScalingPolicy::centered(full, width.round(), height.round()),
+        )
+      }
+      ScalingPolicy::FixedHeight => {
+        let mut fitted = camera.to_owned();
+        fitted.width = camera.height * fb_width / fb_height;
+        (fitted, full)
+      }
+      ScalingPolicy::IntegerScale => {
+        let scale = (fb_width / camera.width)
+          .min(fb_height / camera.height)
+          .floor()
+          .max(1.0);
+        (
+          camera.to_owned(),
+          ScalingPolicy::centered(
+            full,
+            (camera.width * scale).round(),
+            (camera.height * scale).round(),
+          ),
+        )
+      }
+    }
+  }
+
+  fn centered(full: Viewport, width: f32, height: f32) -> Viewport {
+    Viewport::new(
+      (full.width as i32 - width as i32) / 2,
+      (full.height as i32 - height as i32) / 2,
+      width as u32,
+      height as u32,
+    )
+  }
+}
+
+impl Default for ScalingPolicy {
+  fn default() -> Self {
+    ScalingPolicy::Stretch
+  }
+}
@@ -0,0 +1,319 @@
+use std::{
+  collections::{HashMap, HashSet},
+  path::Path,
+};
+
+use crate::{
+  general::color::Color,
+  gl_utils::{
+    gl_texture::Texture, gl_translation::DrawingMode, image_data::ImageData,
+  },
+  math::matrix::{IdentityMatrix, Matrix},
+};
+
+use super::{
+  backend::{RenderBackend, DEFAULT_MAX_TEXTURE_UNITS},
+  drawer::{
+    VERTEX_COLOR_OFFSET, VERTEX_POSITION_OFFSET, VERTEX_SIZE,
+    VERTEX_TEXTURE_CORDS_OFFSET, VERTEX_TEXTURE_ID_OFFSET,
+    VERTEX_TRANSFORM_OFFSET,
+  },
+  scaling::Viewport,
+};
+
+/// Vertex after it has been transformed into framebuffer pixels
+#[derive(Debug, Copy, Clone)]
+struct RasterVertex {
+  x: f32,
+  y: f32,
+  color: [f32; 4],
+  texture_cords: [f32; 2],
+  texture_unit: i32,
+}
+
+fn to_channel(value: f32) -> u8 {
+  (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn edge(a: &RasterVertex, b: &RasterVertex, x: f32, y: f32) -> f32 {
+  (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+}
+
+/// Pure Rust rasterizer that renders into an RGBA framebuffer.
+///
+/// Mirrors what the default shaders do: the transform and projection are
+/// applied to the position the same way the vertex shader does, textures are sampled with
+/// nearest filtering and multiplied by the vertex color, and the result is
+/// alpha blended into the framebuffer.
+pub struct SoftwareBackend {
+  framebuffer: ImageData,
+  textures: HashMap<i32, ImageData>,
+  /// Ids of textures whose image couldn't be loaded, they're drawn without
+  /// it
+  failed_textures: HashSet<i32>,
+  /// Texture id bound to every texture unit
+  bound_textures: Vec<i32>,
+  max_texture_units: usize,
+  projection: Matrix<f32>,
+  viewport: Viewport,
+}
+
+impl SoftwareBackend {
+  pub fn new(width: u32, height: u32) -> SoftwareBackend {
+    SoftwareBackend {
+      framebuffer: ImageData::new(
+        width,
+        height,
+        vec![0; (width * height * 4) as usize],
+      ),
+      textures: HashMap::new(),
+      failed_textures: HashSet::new(),
+      bound_textures: vec![],
+      max_texture_units: DEFAULT_MAX_TEXTURE_UNITS,
+      projection: Matrix::generate_identity(4),
+      viewport: Viewport::full(width, height),
+    }
+  }
+
+  pub fn get_framebuffer(&self) -> &ImageData {
+    &self.framebuffer
+  }
+
+  /// Uses `image` for the texture with the given id
+  pub fn load_texture_data(&mut self, texture_id: i32, image: ImageData) {
+    self.textures.insert(texture_id, image);
+  }
+
+  /// Pretends to have fewer (or more) texture units, e.g. to test frames
+  /// that need more textures than a GPU could bind at once
+  pub fn set_max_texture_units(&mut self, max_texture_units: usize) {
+    self.max_texture_units = max_texture_units.max(1);
+  }
+
+  fn to_raster_vertex(&self, vertex: &[f32]) -> RasterVertex {
+    let x = vertex[VERTEX_POSITION_OFFSET];
+    let y = vertex[VERTEX_POSITION_OFFSET + 1];
+
+    // The shader reads the transform as four column vectors
+    let trans = &vertex[VERTEX_TRANSFORM_OFFSET..VERTEX_TRANSFORM_OFFSET + 16];
+    let transformed = [
+      trans[0] * x + trans[4] * y + trans[12],
+      trans[1] * x + trans[5] * y + trans[13],
+      trans[2] * x + trans[6] * y + trans[14],
+      trans[3] * x + trans[7] * y + trans[15],
+    ];
+
+    // The projection is row major
+    let projection = self.projection.get_inner_ptr();
+    let project = |row: usize| {
+      (0..4)
+        .map(|column| projection[row * 4 + column] * transformed[column])
+        .sum::<f32>()
+    };
+    let (clip_x, clip_y, clip_w) = (project(0), project(1), project(3));
+
+    let (ndc_x, ndc_y) = (clip_x / clip_w, clip_y / clip_w);
+
+    // The viewport's origin is the bottom left, the framebuffer's the top left
+    let viewport = &self.viewport;
+    let viewport_top =
+      self.framebuffer.height as i32 - (viewport.y + viewport.height as i32);
+
+    RasterVertex {
+      x: viewport.x as f32 + (ndc_x + 1.0) / 2.0 * viewport.width as f32,
+      y: viewport_top as f32 + (1.0 - ndc_y) / 2.0 * viewport.height as f32,
+      color: [
+        vertex[VERTEX_COLOR_OFFSET],
+        vertex[VERTEX_COLOR_OFFSET + 1],
+        vertex[VERTEX_COLOR_OFFSET + 2],
+        vertex[VERTEX_COLOR_OFFSET + 3],
+      ],
+      texture_cords: [
+        vertex[VERTEX_TEXTURE_CORDS_OFFSET],
+        vertex[VERTEX_TEXTURE_CORDS_OFFSET + 1],
+      ],
+      texture_unit: vertex[VERTEX_TEXTURE_ID_OFFSET] as i32,
+    }
+  }
+
+  fn sample(&self, texture_unit: i32, u: f32, v: f32) -> [f32; 4] {
+    let texture = usize::try_from(texture_unit)
+      .ok()
+      .and_then(|unit| self.bound_textures.get(unit))
+      .and_then(|texture_id| self.textures.get(texture_id));
+
+    match texture {
+      Some(texture) => {
+        let x = (u.clamp(0.0, 1.0) * texture.width as f32) as u32;
+        let y = (v.clamp(0.0, 1.0) * texture.height as f32) as u32;
+        let [r, g, b, a] = texture.get_pixel(x, y);
+        [
+          r as f32 / 255.0,
+          g as f32 / 255.0,
+          b as f32 / 255.0,
+          a as f32 / 255.0,
+        ]
+      }
+      None => [1.0, 1.0, 1.0, 1.0],
+    }
+  }
+
+  fn blend(&mut self, x: u32, y: u32, color: [f32; 4]) {
+    let idx = ((y * self.framebuffer.width + x) * 4) as usize;
+    let alpha = color[3].clamp(0.0, 1.0);
+    for channel in 0..4 {
+      let dst = self.framebuffer.pixels[idx + channel] as f32 / 255.0;
+      let src = color[channel];
+      self.framebuffer.pixels[idx + channel] =
+        to_channel(src * alpha + dst * (1.0 - alpha));
+    }
+  }
+
+  fn rasterize_triangle(
+    &mut self,
+    a: RasterVertex,
+    b: RasterVertex,
+    c: RasterVertex,
+  ) {
+    let area = edge(&a, &b, c.x, c.y);
+    if area == 0.0 {
+      return;
+    }
+
+    // Everything outside of the viewport gets clipped, like it would be
+    // outside of normalized device coordinates
+    let viewport = &self.viewport;
+    let viewport_top =
+      self.framebuffer.height as i32 - (viewport.y + viewport.height as i32);
+    let left = viewport.x.max(0) as f32;
+    let right = ((viewport.x + viewport.width as i32) as f32)
+      .min(self.framebuffer.width as f32);
+    let top = viewport_top.max(0) as f32;
+    let bottom = ((viewport_top + viewport.height as i32) as f32)
+      .min(self.framebuffer.height as f32);
+
+    let min_x = a.x.min(b.x).min(c.x).floor().clamp(left, right) as u32;
+    let max_x = a.x.max(b.x).max(c.x).ceil().clamp(left, right) as u32;
+    let min_y = a.y.min(b.y).min(c.y).floor().clamp(top, bottom) as u32;
+    let max_y = a.y.max(b.y).max(c.y).ceil().clamp(top, bottom) as u32;
+
+    for py in min_y..max_y {
+      for px in min_x..max_x {
+        let (sample_x, sample_y) = (px as f32 + 0.5, py as f32 + 0.5);
+
+        let wa = edge(&b, &c, sample_x, sample_y) / area;
+        let wb = edge(&c, &a, sample_x, sample_y) / area;
+        let wc = edge(&a, &b, sample_x, sample_y) / area;
+
+        if wa < 0.0 || wb < 0.0 || wc < 0.0 {
+          continue;
+        }
+
+        let u = a.texture_cords[0] * wa
+          + b.texture_cords[0] * wb
+          + c.texture_cords[0] * wc;
+        let v = a.texture_cords[1] * wa
+          + b.texture_cords[1] * wb
+          + c.texture_cords[1] * wc;
+
+        let base_texture = self.sample(a.texture_unit, u, v);
+        let mut color = [0.0; 4];
+        for channel in 0..4 {
+          let vertex_color = a.color[channel] * wa
+            + b.color[channel] * wb
+            + c.color[channel] * wc;
+          color[channel] = base_texture[channel] * vertex_color;
+        }
+
+        self.blend(px, py, color);
+      }
+    }
+  }
+}
+
+impl RenderBackend for SoftwareBackend {
+  fn clear(&mut self, color: Color) {
+    let pixel = [
+      to_channel(color.r),
+      to_channel(color.g),
+      to_channel(color.b),
+      to_channel(color.a),
+    ];
+    for chunk in self.framebuffer.pixels.chunks_mut(4) {
+      chunk.copy_from_slice(&pixel);
+    }
+  }
+
+  fn load_textures(&mut self, textures: &[Texture], asset_root: &Path) {
+    for texture in textures {
+      if texture.texture_id < 0
+        || self.textures.contains_key(&texture.texture_id)
+        || self.failed_textures.contains(&texture.texture_id)
+      {
+        continue;
+      }
+      match texture.load_image_data(asset_root) {
+        Ok(image) => {
+          self.textures.insert(texture.texture_id, image);
+        }
+        Err(error) => {
+          eprintln!("Could not load texture {:?}: {error}", texture.image_name);
+          self.failed_textures.insert(texture.texture_id);
+        }
+      }
+    }
+  }
+
+  fn get_max_texture_units(&self) -> usize {
+    self.max_texture_units
+  }
+
+  fn bind_textures(&mut self, textures: &[Texture]) {
+    self.bound_textures =
+      textures.iter().map(|texture| texture.texture_id).collect();
+  }
+
+  fn release_textures(&mut self, texture_ids: &[i32]) {
+    for texture_id in texture_ids {
+      self.textures.remove(texture_id);
+      self.failed_textures.remove(texture_id);
+    }
+  }
+
+  fn set_projection(&mut self, projection: &Matrix<f32>) {
+    self.projection = projection.to_owned();
+  }
+
+  fn set_viewport(&mut self, viewport: Viewport) {
+    self.viewport = viewport;
+  }
+
+  fn get_framebuffer_size(&self) -> Option<(u32, u32)> {
+    Some((self.framebuffer.width, self.framebuffer.height))
+  }
+
+  fn draw(&mut self, mode: DrawingMode, vertices: &[f32], elements: &[i32]) {
+    match mode {
+      DrawingMode::Triangles => {
+        for triangle in elements.chunks(3) {
+          if triangle.len() < 3 {
+            break;
+          }
+          let corners = triangle
+            .iter()
+            .map(|element| {
+              let start = *element as usize * VERTEX_SIZE;
+              self.to_raster_vertex(&vertices[start..start + VERTEX_SIZE])
+            })
+            .collect::<Vec<RasterVertex>>();
+
+          self.rasterize_triangle(corners[0], corners[1], corners[2]);
+        }
+      }
+    }
+  }
+
+  fn read_pixels(&self) -> Option<ImageData> {
+    Some(self.framebuffer.to_owned())
+  }
+}
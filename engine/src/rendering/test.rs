@@ -0,0 +1,1324 @@
+#[cfg(test)]
+mod software_backend_tests {
+  use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+  };
+
+  use crate::{
+    math::matrix::Matrix,
+    premade_components::{Animation, AnimationComponent, AnimationMode},
+    Bounds, BoundsBehavior, BowTie, Camera2D, Circle, Color, Direction,
+    DrawingMode, Entity, HollowRectangle, ImageData, ImageError, Material,
+    MaterialAttribute, Rectangle, RenderBackend, RenderLayer, ScalingPolicy,
+    Shape, SoftwareBackend, Sprite, SpriteSheet, StandardComponent,
+    StandardEntity, Texture, TextureAtlasBuilder, TextureOptions, TextureRegion,
+    UniformValue, Viewport, COLORS, VERTEX_SIZE,
+  };
+
+  const RED: [u8; 4] = [255, 0, 0, 255];
+  const WHITE: [u8; 4] = [255, 255, 255, 255];
+  const BLUE: [u8; 4] = [0, 0, 255, 255];
+  const GREEN: [u8; 4] = [0, 255, 0, 255];
+
+  #[test]
+  fn renders_rectangle_into_framebuffer() {
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(10, 10)));
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(-1.0, 1.0, 1.0, 1.0, COLORS::Red.into()),
+        Texture::none(),
+      ),
+      0.0,
+    ));
+
+    bowtie.step(1);
+
+    let frame = bowtie.read_pixels().unwrap();
+    assert_eq!(frame.get_pixel(0, 0), RED);
+    assert_eq!(frame.get_pixel(4, 4), RED);
+    assert_eq!(frame.get_pixel(5, 5), WHITE);
+    assert_eq!(frame.get_pixel(9, 0), WHITE);
+  }
+
+  #[test]
+  fn samples_textures() {
+    let texture = Texture::new("software_test", TextureOptions::default());
+    let mut backend = SoftwareBackend::new(4, 4);
+    backend.load_texture_data(
+      texture.texture_id,
+      ImageData::new(2, 1, [RED, BLUE].concat()),
+    );
+
+    let mut bowtie = BowTie::headless_with_backend(Box::new(backend));
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(-1.0, 1.0, 2.0, 2.0, COLORS::White.into()),
+        texture,
+      ),
+      0.0,
+    ));
+
+    bowtie.step(1);
+
+    let frame = bowtie.read_pixels().unwrap();
+    assert_eq!(frame.get_pixel(0, 3), RED);
+    assert_eq!(frame.get_pixel(3, 0), BLUE);
+  }
+
+  /// Reference frames, checked in next to the crate's manifest
+  const REFERENCE_IMAGES: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/reference_images");
+
+  /// Compares the frame with the checked in reference image of that name.
+  /// Running the tests with `BOWTIE_UPDATE_REFERENCES` set rewrites the
+  /// reference instead, review the new image before committing it.
+  fn assert_matches_reference(frame: &ImageData, name: &str) {
+    let location = Path::new(REFERENCE_IMAGES).join(format!("{name}.png"));
+    if std::env::var_os("BOWTIE_UPDATE_REFERENCES").is_some() {
+      frame.save_png(location.to_str().unwrap()).unwrap();
+    }
+
+    let reference = ImageData::from_png_file(&location).unwrap();
+    let actual = std::env::temp_dir().join(format!("bowtie_{name}.png"));
+    if (frame.width, frame.height) != (reference.width, reference.height)
+      || frame.pixels != reference.pixels
+    {
+      frame.save_png(actual.to_str().unwrap()).unwrap();
+      panic!(
+        "Frame differs from {}, it was saved to {}",
+        location.display(),
+        actual.display()
+      );
+    }
+  }
+
+  #[test]
+  fn matches_reference_scene() {
+    let checker = Texture::from_image_data(
+      "reference_checker",
+      ImageData::new(2, 2, [RED, BLUE, BLUE, RED].concat()),
+      TextureOptions::default(),
+    );
+
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(16, 16)));
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(-1.0, 1.0, 1.0, 1.0, COLORS::White.into()),
+        checker,
+      ),
+      0.0,
+    ));
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        Circle::new(-0.25, 0.25, 1.0, 16, COLORS::Green.into()),
+        Texture::none(),
+      ),
+      0.0,
+    ));
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        HollowRectangle::new(0.0, 0.0, 1.0, 1.0, 0.25, COLORS::Black.into()),
+        Texture::none(),
+      ),
+      0.0,
+    ));
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(-1.0, -0.5, 2.0, 0.5, Color::new(0.0, 0.0, 1.0, 0.5)),
+        Texture::none(),
+      ),
+      0.0,
+    ));
+
+    bowtie.step(1);
+
+    assert_matches_reference(&bowtie.read_pixels().unwrap(), "scene");
+  }
+
+  #[test]
+  fn interpolates_between_fixed_updates() {
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(10, 10)));
+    let mut entity = StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(-1.0, 1.0, 0.2, 2.0, COLORS::Red.into()),
+        Texture::none(),
+      ),
+      0.0,
+    );
+    entity.load_components(StandardComponent::new(
+      Arc::new(|entity, _store, _context| {
+        entity.move_in_direction(Direction::Right, 0.4);
+      }),
+      "move_right",
+      HashMap::new(),
+    ));
+    bowtie.load_entity(entity);
+
+    // Half an update's worth of time is left over after the first update
+    bowtie.set_time_scale(0.5);
+    bowtie.step(3);
+    assert_eq!(bowtie.get_time().get_alpha(), 0.5);
+
+    // Drawn halfway between x = -1.0 and x = -0.6
+    let frame = bowtie.read_pixels().unwrap();
+    assert_eq!(frame.get_pixel(0, 5), WHITE);
+    assert_eq!(frame.get_pixel(1, 5), RED);
+    assert_eq!(frame.get_pixel(2, 5), WHITE);
+  }
+
+  #[test]
+  fn does_not_interpolate_across_wrapping_bounds() {
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(10, 10)));
+    let mut entity = StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(0.8, 1.0, 0.2, 2.0, COLORS::Red.into()),
+        Texture::none(),
+      ),
+      0.0,
+    );
+    entity.set_bounds(Some(Bounds::screen(BoundsBehavior::Wrap)));
+    entity.load_components(StandardComponent::new(
+      Arc::new(|entity, _store, _context| {
+        entity.move_in_direction(Direction::Right, 0.4);
+      }),
+      "move_right",
+      HashMap::new(),
+    ));
+    bowtie.load_entity(entity);
+
+    bowtie.set_time_scale(0.5);
+    bowtie.step(3);
+    assert_eq!(bowtie.get_time().get_alpha(), 0.5);
+
+    // Wrapped to x = -1.0, not drawn halfway back across the screen
+    let frame = bowtie.read_pixels().unwrap();
+    assert_eq!(frame.get_pixel(0, 5), RED);
+    assert_eq!(frame.get_pixel(5, 5), WHITE);
+  }
+
+  /// Right triangle with its right angle at the top left corner
+  #[derive(Debug, Clone)]
+  struct Triangle {
+    x: f32,
+    y: f32,
+    size: f32,
+    color: Color,
+  }
+
+  impl Shape for Triangle {
+    fn get_x(&self) -> f32 {
+      self.x
+    }
+
+    fn get_y(&self) -> f32 {
+      self.y
+    }
+
+    fn set_x(&mut self, x: f32) {
+      self.x = x;
+    }
+
+    fn set_y(&mut self, y: f32) {
+      self.y = y;
+    }
+
+    fn get_width(&self) -> f32 {
+      self.size
+    }
+
+    fn get_height(&self) -> f32 {
+      self.size
+    }
+
+    fn set_height(&mut self, height: f32) {
+      self.size = height;
+    }
+
+    fn set_width(&mut self, width: f32) {
+      self.size = width;
+    }
+
+    fn get_color(&self) -> Color {
+      self.color
+    }
+
+    fn set_color(&mut self, color: Color) {
+      self.color = color;
+    }
+
+    fn get_coordinate_corners(&self) -> Vec<[f32; 2]> {
+      vec![
+        [self.x, self.y],
+        [self.x + self.size, self.y],
+        [self.x, self.y - self.size],
+      ]
+    }
+
+    fn get_texture_corners(&self) -> Vec<[f32; 2]> {
+      vec![[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]
+    }
+
+    fn flip_texture_corners_x(&mut self) {}
+
+    fn flip_texture_corners_y(&mut self) {}
+  }
+
+  #[test]
+  fn renders_any_shape() {
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(10, 10)));
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        Triangle {
+          x: -1.0,
+          y: 1.0,
+          size: 2.0,
+          color: COLORS::Red.into(),
+        },
+        Texture::none(),
+      ),
+      0.0,
+    ));
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(0.6, -0.6, 0.4, 0.4, COLORS::Green.into()),
+        Texture::none(),
+      ),
+      0.0,
+    ));
+
+    bowtie.step(1);
+
+    let frame = bowtie.read_pixels().unwrap();
+    assert_eq!(frame.get_pixel(0, 0), RED);
+    assert_eq!(frame.get_pixel(8, 0), RED);
+    assert_eq!(frame.get_pixel(0, 8), RED);
+    assert_eq!(frame.get_pixel(6, 6), WHITE);
+    assert_eq!(frame.get_pixel(9, 9), GREEN);
+  }
+
+  #[test]
+  fn renders_hollow_rectangle_outline() {
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(10, 10)));
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        HollowRectangle::new(-1.0, 1.0, 2.0, 2.0, 0.4, COLORS::Red.into()),
+        Texture::none(),
+      ),
+      0.0,
+    ));
+
+    bowtie.step(1);
+
+    let frame = bowtie.read_pixels().unwrap();
+    for (x, y) in [(0, 0), (1, 5), (5, 1), (9, 9), (8, 4), (4, 8)] {
+      assert_eq!(frame.get_pixel(x, y), RED);
+    }
+    for (x, y) in [(2, 2), (5, 5), (7, 7), (2, 7)] {
+      assert_eq!(frame.get_pixel(x, y), WHITE);
+    }
+  }
+
+  #[test]
+  fn renders_circle() {
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(10, 10)));
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        Circle::new(-1.0, 1.0, 1.0, 32, COLORS::Red.into()),
+        Texture::none(),
+      ),
+      0.0,
+    ));
+
+    bowtie.step(1);
+
+    let frame = bowtie.read_pixels().unwrap();
+    for (x, y) in [(5, 5), (5, 0), (0, 5), (9, 5), (5, 9)] {
+      assert_eq!(frame.get_pixel(x, y), RED);
+    }
+    for (x, y) in [(0, 0), (9, 0), (0, 9), (9, 9)] {
+      assert_eq!(frame.get_pixel(x, y), WHITE);
+    }
+  }
+
+  #[test]
+  fn camera_projects_world_units() {
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(10, 10)));
+
+    // One world unit per pixel, with the origin at the top left
+    let mut camera = Camera2D::new(10.0, 10.0);
+    camera.set_position(5.0, -5.0);
+    bowtie.set_camera(camera);
+
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(2.0, -2.0, 3.0, 3.0, COLORS::Red.into()),
+        Texture::none(),
+      ),
+      0.0,
+    ));
+
+    bowtie.step(1);
+    let frame = bowtie.read_pixels().unwrap();
+    assert_eq!(frame.get_pixel(1, 1), WHITE);
+    assert_eq!(frame.get_pixel(2, 2), RED);
+    assert_eq!(frame.get_pixel(4, 4), RED);
+    assert_eq!(frame.get_pixel(5, 5), WHITE);
+
+    // Scrolling right moves the entity left on screen
+    bowtie.get_camera_mut().move_by(2.0, 0.0);
+    bowtie.step(1);
+    let frame = bowtie.read_pixels().unwrap();
+    assert_eq!(frame.get_pixel(0, 2), RED);
+    assert_eq!(frame.get_pixel(3, 2), WHITE);
+
+    // Zooming in doubles its size around the camera's position,
+    // so it now spans from off screen to the middle
+    bowtie.set_camera(Camera2D {
+      zoom: 2.0,
+      ..camera
+    });
+    bowtie.step(1);
+    let frame = bowtie.read_pixels().unwrap();
+    assert_eq!(frame.get_pixel(0, 0), RED);
+    assert_eq!(frame.get_pixel(4, 4), RED);
+    assert_eq!(frame.get_pixel(5, 5), WHITE);
+    assert_eq!(frame.get_pixel(6, 0), WHITE);
+  }
+
+  #[test]
+  fn camera_maps_back_to_world() {
+    let camera = Camera2D {
+      x: 3.0,
+      y: -1.0,
+      width: 8.0,
+      height: 4.0,
+      zoom: 2.0,
+      rotation: 90.0,
+    };
+
+    let projection = camera.get_projection();
+    let (world_x, world_y) = camera.to_world(0.5, 0.25);
+    let ndc_x =
+      projection[0][0] * world_x + projection[0][1] * world_y + projection[0][3];
+    let ndc_y =
+      projection[1][0] * world_x + projection[1][1] * world_y + projection[1][3];
+
+    assert!((ndc_x - 0.5).abs() < 0.0001);
+    assert!((ndc_y - 0.25).abs() < 0.0001);
+    assert_eq!(Camera2D::default().get_visible_size(), (2.0, 2.0));
+  }
+
+  fn load_world_sized_rectangle(bowtie: &mut BowTie) {
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(-1.0, 1.0, 2.0, 2.0, COLORS::Red.into()),
+        Texture::none(),
+      ),
+      0.0,
+    ));
+  }
+
+  #[test]
+  fn stretches_by_default() {
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(20, 10)));
+    assert_eq!(bowtie.get_framebuffer_size(), Some((20, 10)));
+    load_world_sized_rectangle(&mut bowtie);
+
+    bowtie.step(1);
+
+    let frame = bowtie.read_pixels().unwrap();
+    assert_eq!(frame.get_pixel(0, 5), RED);
+    assert_eq!(frame.get_pixel(19, 5), RED);
+  }
+
+  #[test]
+  fn letterbox_keeps_aspect_ratio() {
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(20, 10)));
+    bowtie.set_scaling_policy(ScalingPolicy::Letterbox);
+    load_world_sized_rectangle(&mut bowtie);
+
+    bowtie.step(1);
+
+    // The square world stays square, with bars to both sides
+    let frame = bowtie.read_pixels().unwrap();
+    assert_eq!(frame.get_pixel(4, 5), WHITE);
+    assert_eq!(frame.get_pixel(5, 0), RED);
+    assert_eq!(frame.get_pixel(14, 9), RED);
+    assert_eq!(frame.get_pixel(15, 5), WHITE);
+  }
+
+  #[test]
+  fn fixed_height_shows_more_to_the_sides() {
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(20, 10)));
+    bowtie.set_scaling_policy(ScalingPolicy::FixedHeight);
+    load_world_sized_rectangle(&mut bowtie);
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(1.0, 1.0, 1.0, 2.0, COLORS::Green.into()),
+        Texture::none(),
+      ),
+      0.0,
+    ));
+
+    bowtie.step(1);
+
+    let frame = bowtie.read_pixels().unwrap();
+    assert_eq!(frame.get_pixel(4, 5), WHITE);
+    assert_eq!(frame.get_pixel(5, 5), RED);
+    assert_eq!(frame.get_pixel(14, 5), RED);
+    assert_eq!(frame.get_pixel(17, 5), GREEN);
+  }
+
+  #[test]
+  fn integer_scale_uses_whole_multiples() {
+    let camera = Camera2D::new(4.0, 3.0);
+
+    let (fitted, viewport) = ScalingPolicy::IntegerScale.apply(&camera, 10, 10);
+    assert_eq!(fitted, camera);
+    assert_eq!(viewport, Viewport::new(1, 2, 8, 6));
+
+    // Never scales below one, even if the framebuffer is too small
+    let (_, viewport) = ScalingPolicy::IntegerScale.apply(&camera, 2, 2);
+    assert_eq!(viewport, Viewport::new(-1, 0, 4, 3));
+  }
+
+  #[test]
+  fn zero_sized_cameras_fill_the_framebuffer() {
+    for camera in [Camera2D::new(0.0, 3.0), Camera2D::new(4.0, 0.0)] {
+      for policy in [ScalingPolicy::Letterbox, ScalingPolicy::IntegerScale] {
+        let (_, viewport) = policy.apply(&camera, 10, 8);
+        assert_eq!(viewport, Viewport::full(10, 8));
+      }
+    }
+  }
+
+  #[test]
+  fn draws_by_z_index() {
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(10, 10)));
+
+    let mut player = StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(-0.6, 0.6, 1.2, 1.2, COLORS::Red.into()),
+        Texture::none(),
+      ),
+      0.0,
+    );
+    player.set_layer(RenderLayer::World);
+    bowtie.load_entity(player);
+
+    // Loaded after the player, but still drawn behind it
+    let mut background = StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(-1.0, 1.0, 2.0, 2.0, COLORS::Black.into()),
+        Texture::none(),
+      ),
+      0.0,
+    );
+    background.set_layer(RenderLayer::Background);
+    bowtie.load_entity(background);
+
+    // Same z-index as the player, so drawn over it in load order
+    let mut marker = StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(-0.2, 0.2, 0.4, 0.4, COLORS::Green.into()),
+        Texture::none(),
+      ),
+      0.0,
+    );
+    marker.set_z_index(0);
+    bowtie.load_entity(marker);
+
+    bowtie.step(1);
+
+    let frame = bowtie.read_pixels().unwrap();
+    assert_eq!(frame.get_pixel(0, 0), [0, 0, 0, 255]);
+    assert_eq!(frame.get_pixel(2, 2), RED);
+    assert_eq!(frame.get_pixel(5, 5), GREEN);
+  }
+
+  #[test]
+  fn draws_same_z_index_in_load_order_after_unloading() {
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(10, 10)));
+    let load = |bowtie: &mut BowTie, color: Color, size: f32| {
+      bowtie.load_entity(StandardEntity::new(
+        Sprite::new(
+          Rectangle::new(-size / 2.0, size / 2.0, size, size, color),
+          Texture::none(),
+        ),
+        0.0,
+      ))
+    };
+
+    let removed = load(&mut bowtie, COLORS::Black.into(), 2.0);
+    load(&mut bowtie, COLORS::Green.into(), 0.4);
+    bowtie.unload_entity(removed);
+
+    // Takes over the unloaded entity's slot, but was still loaded last
+    load(&mut bowtie, COLORS::Red.into(), 2.0);
+    bowtie.step(1);
+
+    assert_eq!(bowtie.read_pixels().unwrap().get_pixel(5, 5), RED);
+  }
+
+  fn solid_image(width: u32, height: u32, color: [u8; 4]) -> ImageData {
+    ImageData::new(width, height, color.repeat((width * height) as usize))
+  }
+
+  #[test]
+  fn atlas_packs_images_into_pages() {
+    let mut builder = TextureAtlasBuilder::new(8);
+    builder.add_image("red", solid_image(2, 2, RED));
+    builder.add_image("blue", solid_image(2, 2, BLUE));
+    builder.add_image("green", solid_image(2, 2, GREEN));
+    builder.add_image("white", solid_image(2, 2, WHITE));
+    builder.add_image("wide", solid_image(10, 1, RED));
+
+    let atlas = builder.build();
+
+    // Four padded 4x4 images share a page, the wide one gets its own
+    assert_eq!(atlas.get_pages().len(), 2);
+    let wide = atlas.get_entry("wide").unwrap();
+    assert_eq!(
+      atlas.get_pages()[wide.page]
+        .load_image_data(Path::new("."))
+        .unwrap()
+        .width,
+      12
+    );
+
+    for (name, color) in [("red", RED), ("blue", BLUE), ("green", GREEN)] {
+      let entry = atlas.get_entry(name).unwrap();
+      assert_ne!(entry.page, wide.page);
+
+      // The image and the padding around it both have its color
+      let page = atlas.get_pages()[entry.page]
+        .load_image_data(Path::new("."))
+        .unwrap();
+      let x = (entry.region.x * page.width as f32) as u32;
+      let y = (entry.region.y * page.height as f32) as u32;
+      assert_eq!(entry.region.width * page.width as f32, 2.0);
+      for (offset_x, offset_y) in [(0, 0), (1, 1), (2, 2)] {
+        assert_eq!(page.get_pixel(x + offset_x, y + offset_y), color);
+      }
+      assert_eq!(page.get_pixel(x - 1, y - 1), color);
+    }
+  }
+
+  #[test]
+  fn packed_sprites_render_from_atlas() {
+    let red = Texture::from_image_data(
+      "red",
+      solid_image(1, 1, RED),
+      TextureOptions::default(),
+    );
+    let red_blue = Texture::from_image_data(
+      "red_blue",
+      ImageData::new(2, 1, [RED, BLUE].concat()),
+      TextureOptions::default(),
+    );
+
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(10, 10)));
+    let red_id = bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(-1.0, 1.0, 1.0, 2.0, COLORS::White.into()),
+        red,
+      ),
+      0.0,
+    ));
+
+    // Only shows the right half of its texture
+    let mut blue_sprite = Sprite::new(
+      Rectangle::new(0.0, 1.0, 1.0, 2.0, COLORS::White.into()),
+      red_blue,
+    );
+    blue_sprite.set_texture_region(TextureRegion::new(0.5, 0.0, 0.5, 1.0));
+    let blue_id = bowtie.load_entity(StandardEntity::new(blue_sprite, 0.0));
+
+    let atlas = bowtie.pack_textures(64).unwrap();
+    assert_eq!(atlas.get_pages().len(), 1);
+
+    let texture_of = |id| {
+      let entity: &StandardEntity = bowtie.get_entity(id).unwrap();
+      entity.get_sprite().texture.texture_id
+    };
+    assert_eq!(texture_of(red_id), texture_of(blue_id));
+
+    bowtie.step(1);
+
+    let frame = bowtie.read_pixels().unwrap();
+    assert_eq!(frame.get_pixel(0, 5), RED);
+    assert_eq!(frame.get_pixel(4, 5), RED);
+    assert_eq!(frame.get_pixel(5, 5), BLUE);
+    assert_eq!(frame.get_pixel(9, 5), BLUE);
+  }
+
+  #[test]
+  fn packed_animations_show_their_sheet_frames() {
+    let green = Texture::from_image_data(
+      "green",
+      solid_image(1, 1, GREEN),
+      TextureOptions::default(),
+    );
+    let red_blue = Texture::from_image_data(
+      "red_blue",
+      ImageData::new(2, 1, [RED, BLUE].concat()),
+      TextureOptions::default(),
+    );
+    let sheet = SpriteSheet::grid(red_blue.to_owned(), 2, 1);
+    let mut animation = AnimationComponent::new(sheet.to_owned());
+    animation.add_animation(
+      "blink",
+      Animation::new(vec![0, 1], 10.0, AnimationMode::Loop),
+    );
+
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(10, 10)));
+    bowtie.set_fixed_update_rate(10.0);
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(-1.0, 1.0, 1.0, 2.0, COLORS::White.into()),
+        green,
+      ),
+      0.0,
+    ));
+    let mut blinking = StandardEntity::new(
+      Sprite::from_sheet(
+        Rectangle::new(0.0, 1.0, 1.0, 2.0, COLORS::White.into()),
+        &sheet,
+        0,
+      ),
+      0.0,
+    );
+    blinking.load_components(animation.component());
+    bowtie.load_entity(blinking);
+
+    bowtie.pack_textures(64).unwrap();
+
+    for color in [RED, BLUE, RED] {
+      bowtie.step(1);
+      let frame = bowtie.read_pixels().unwrap();
+      assert_eq!(frame.get_pixel(0, 5), GREEN);
+      for x in 5..10 {
+        assert_eq!(frame.get_pixel(x, 5), color);
+      }
+    }
+  }
+
+  #[test]
+  fn creates_textures_from_memory() {
+    let mismatched =
+      Texture::from_rgba("short", 2, 2, RED.to_vec(), TextureOptions::default());
+    assert!(matches!(mismatched, Err(ImageError::SizeMismatch { .. })));
+    for (width, height) in [(1 << 16, 1 << 16), (u32::MAX, u32::MAX)] {
+      let huge = Texture::from_rgba(
+        "huge",
+        width,
+        height,
+        vec![],
+        TextureOptions::default(),
+      );
+      assert!(matches!(huge, Err(ImageError::SizeMismatch { .. })));
+    }
+    assert!(matches!(
+      Texture::from_bytes("junk", &[1, 2, 3], TextureOptions::default()),
+      Err(ImageError::Decode(_))
+    ));
+
+    let mut png = vec![];
+    ImageData::new(2, 1, [RED, BLUE].concat())
+      .write_png(&mut png)
+      .unwrap();
+    let texture =
+      Texture::from_bytes("red_blue", &png, TextureOptions::default()).unwrap();
+
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(4, 4)));
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(-1.0, 1.0, 2.0, 2.0, COLORS::White.into()),
+        texture,
+      ),
+      0.0,
+    ));
+
+    bowtie.step(1);
+
+    let frame = bowtie.read_pixels().unwrap();
+    assert_eq!(frame.get_pixel(0, 0), RED);
+    assert_eq!(frame.get_pixel(3, 3), BLUE);
+  }
+
+  #[test]
+  fn reads_images_from_asset_root() {
+    let asset_root = std::env::temp_dir().join("bowtie_asset_root_test");
+    fs::create_dir_all(asset_root.join("images")).unwrap();
+    solid_image(1, 1, GREEN)
+      .save_png(asset_root.join("images/green.png").to_str().unwrap())
+      .unwrap();
+
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(2, 2)));
+    bowtie.set_asset_root(&asset_root);
+
+    assert!(bowtie
+      .load_texture("green", TextureOptions::default())
+      .is_ok());
+    assert!(matches!(
+      bowtie.load_texture("missing", TextureOptions::default()),
+      Err(ImageError::Io { .. })
+    ));
+
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(-1.0, 1.0, 2.0, 2.0, COLORS::White.into()),
+        Texture::new("green", TextureOptions::default()),
+      ),
+      0.0,
+    ));
+
+    bowtie.step(1);
+
+    assert_eq!(bowtie.read_pixels().unwrap().get_pixel(1, 1), GREEN);
+  }
+
+  #[test]
+  fn hot_reloads_changed_images() {
+    let asset_root = std::env::temp_dir().join("bowtie_hot_reload_test");
+    let location = asset_root.join("images/hot.png");
+    fs::create_dir_all(asset_root.join("images")).unwrap();
+    solid_image(1, 1, RED)
+      .save_png(location.to_str().unwrap())
+      .unwrap();
+
+    let mut bowtie =
+      BowTie::headless_with_backend(Box::new(SoftwareBackend::new(2, 2)));
+    bowtie.set_asset_root(&asset_root);
+    bowtie.set_hot_reload(true);
+    bowtie.load_entity(StandardEntity::new(
+      Sprite::new(
+        Rectangle::new(-1.0, 1.0, 2.0, 2.0, COLORS::White.into()),
+        Texture::new("hot", TextureOptions::default()),
+      ),
+      0.0,
+    ));
+
+    bowtie.step(1);
+    assert_eq!(bowtie.read_pixels().unwrap().get_pixel(0, 0), RED);
+
+    // Broken files keep the old image
+    fs::write(&location, [1, 2, 3]).unwrap();
+    let touch = |seconds| {
+      fs::File::options()
+        .write(true)
+        .open(&location)
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(seconds))
+        .unwrap();
+    };
+    touch(10);
+    bowtie.reload_changed_assets();
+    bowtie.step(1);
+    assert_eq!(bowtie.read_pixels().unwrap().get_pixel(0, 0), RED);
+
+    let errors = bowtie.take_reload_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].0, "hot");
+    assert!(matches!(errors[0].1, ImageError::Decode(_)));
+    assert!(bowtie.take_reload_errors().is_empty());
+
+    solid_image(1, 1, BLUE)
+      .save_png(location.to_str().unwrap())
+      .unwrap();
+    touch(20);
+    bowtie.reload_changed_assets();
+    bowtie.step(1);
+    assert_eq!(bowtie.read_pixels().unwrap().get_pixel(0, 0), BLUE);
+  }
+
+  #[test]
+  fn splits_draw_calls_when_out_of_texture_units() {
+    let mut backend = SoftwareBackend::new(6, 2);
+    backend.set_max_texture_units(2);
+
+    let mut bowtie = BowTie::headless_with_backend(Box::new(backend));
+    for (index, color) in [RED, GREEN, BLUE, RED, WHITE, GREEN]
+      .into_iter()
+      .enumerate()
+    {
+      let texture = Texture::from_image_data(
+        format!("unit_test_{index}").as_str(),
+        solid_image(1, 1, color),
+        TextureOptions::default(),
+      );
+      bowtie.load_entity(StandardEntity::new(
+        Sprite::new(
+          Rectangle::new(
+            -1.0 + index as f32 / 3.0,
+            1.0,
+            1.0 / 3.0,
+            2.0,
+            COLORS::White.into(),
+          ),
+          texture,
+        ),
+        0.0,
+      ));
+    }
+
+    bowtie.step(1);
+
+    let frame = bowtie.read_pixels().unwrap();
+    for (x, color) in [RED, GREEN, BLUE, RED, WHITE, GREEN].iter().enumerate() {
+      assert_eq!(frame.get_pixel(x as u32, 1), *color);
+    }
+  }
+
+  /// Backend that only remembers which textures it was told to release
+  struct ReleaseRecorder {
+    released: Arc<Mutex<Vec<i32>>>,
+  }
+
+  impl RenderBackend for ReleaseRecorder {
+    fn clear(&mut self, _color: Color) {}
+    fn load_textures(&mut self, _textures: &[Texture], _root: &Path) {}
+    fn bind_textures(&mut self, _textures: &[Texture]) {}
+    fn release_textures(&mut self, texture_ids: &[i32]) {
+      self.released.lock().unwrap().extend(texture_ids);
+    }
+    fn set_projection(&mut self, _projection: &Matrix<f32>) {}
+    fn set_viewport(&mut self, _viewport: Viewport) {}
+    fn draw(&mut self, _mode: DrawingMode, _: &[f32], _: &[i32]) {}
+  }
+
+  #[test]
+  fn releases_textures_once_unused() {
+    let released = Arc::new(Mutex::new(vec![]));
+    let mut bowtie = BowTie::headless_with_backend(Box::new(ReleaseRecorder {
+      released: released.clone(),
+    }));
+
+    let shared = Texture::from_image_data(
+      "shared",
+      solid_image(1, 1, RED),
+      TextureOptions::default(),
+    );
+    let single = Texture::from_image_data(
+      "single",
+      solid_image(1, 1, BLUE),
+      TextureOptions::default(),
+    );
+    let (shared_id, single_id) = (shared.texture_id, single.texture_id);
+
+    let load = |bowtie: &mut BowTie, texture: Texture| {
+      bowtie.load_entity(StandardEntity::new(
+        Sprite::new(
+          Rectangle::new(-1.0, 1.0, 1.0, 1.0, COLORS::White.into()),
+          texture,
+        ),
+        0.0,
+      ))
+    };
+    let first = load(&mut bowtie, Texture::from(&shared));
+    let second = load(&mut bowtie, shared);
+    let other = load(&mut bowtie, single);
+
+    bowtie.step(1);
+    assert!(released.lock().unwrap().is_empty());
+
+    bowtie.unload_entity(other);
+    bowtie.unload_entity(first);
+    bowtie.step(1);
+    assert_eq!(*released.lock().unwrap(), vec![single_id]);
+
+    bowtie.unload_entity(second);
+    bowtie.step(1);
+    assert_eq!(*released.lock().unwrap(), vec![single_id, shared_id]);
+  }
+
+  /// Material name and uniforms of every draw call and how many vertices
+  /// it had, `None` for the built-in shaders
+  type DrawnMaterial = (Option<(String, Vec<(String, UniformValue)>)>, usize);
+
+  /// Backend that only remembers the materials it draws with and releases
+  #[derive(Default)]
+  struct MaterialRecorder {
+    material: Option<(String, Vec<(String, UniformValue)>)>,
+    draws: Arc<Mutex<Vec<DrawnMaterial>>>,
+    released: Arc<Mutex<Vec<i32>>>,
+  }
+
+  impl RenderBackend for MaterialRecorder {
+    fn clear(&mut self, _color: Color) {}
+    fn load_textures(&mut self, _textures: &[Texture], _root: &Path) {}
+    fn bind_textures(&mut self, _textures: &[Texture]) {}
+    fn release_textures(&mut self, _texture_ids: &[i32]) {}
+    fn use_material(&mut self, material: Option<&Material>) {
+      self.material = material.map(|material| {
+        let uniforms = material
+          .get_uniforms()
+          .iter()
+          .map(|(name, value)| (name.to_owned(), value.to_owned()))
+          .collect();
+        (material.name.to_owned(), uniforms)
+      });
+    }
+    fn release_materials(&mut self, material_ids: &[i32]) {
+      self.released.lock().unwrap().extend(material_ids);
+    }
+    fn set_projection(&mut self, _projection: &Matrix<f32>) {}
+    fn set_viewport(&mut self, _viewport: Viewport) {}
+    fn draw(&mut self, _mode: DrawingMode, vertices: &[f32], _: &[i32]) {
+      self
+        .draws
+        .lock()
+        .unwrap()
+        .push((self.material.to_owned(), vertices.len() / VERTEX_SIZE));
+    }
+  }
+
+  fn material_entity<'a>(
+    material: Option<Material>,
+    z_index: i32,
+  ) -> StandardEntity<'a> {
+    let mut sprite = Sprite::new(
+      Rectangle::new(-1.0, 1.0, 1.0, 1.0, COLORS::White.into()),
+      Texture::none(),
+    )
+    .boxed();
+    sprite.set_material(material);
+
+    let mut entity = StandardEntity::new(sprite, 0.0);
+    entity.set_z_index(z_index);
+    entity
+  }
+
+  fn dissolve_material() -> Material {
+    Material::new(
+      "dissolve",
+      "void main() {}",
+      "void main() {}",
+      MaterialAttribute::default_layout(),
+    )
+  }
+
+  #[test]
+  fn batches_sprites_by_material() {
+    let draws = Arc::new(Mutex::new(vec![]));
+    let mut bowtie = BowTie::headless_with_backend(Box::new(MaterialRecorder {
+      draws: draws.clone(),
+      ..MaterialRecorder::default()
+    }));
+
+    let outline = Material::new(
+      "outline",
+      "void main() {}",
+      "void main() {}",
+      MaterialAttribute::default_layout(),
+    );
+    let mut dissolve = dissolve_material();
+    dissolve.set_uniform("amount", UniformValue::Float(0.5));
+    let mut dissolved = dissolve.to_owned();
+    dissolved.set_uniform("amount", UniformValue::Float(1.0));
+
+    bowtie.load_entity(material_entity(Some(outline.to_owned()), 0));
+    bowtie.load_entity(material_entity(Some(outline), 0));
+    bowtie.load_entity(material_entity(None, 0));
+    bowtie.load_entity(material_entity(None, 0));
+    bowtie.load_entity(material_entity(Some(dissolve.to_owned()), 1));
+    bowtie.load_entity(material_entity(Some(dissolve), 1));
+    bowtie.load_entity(material_entity(Some(dissolved), 1));
+
+    bowtie.step(1);
+
+    let half = vec![(String::from("amount"), UniformValue::Float(0.5))];
+    let full = vec![(String::from("amount"), UniformValue::Float(1.0))];
+    assert_eq!(
+      *draws.lock().unwrap(),
+      vec![
+        (Some((String::from("outline"), vec![])), 8),
+        (None, 8),
+        (Some((String::from("dissolve"), half)), 8),
+        (Some((String::from("dissolve"), full)), 4),
+      ]
+    );
+  }
+
+  #[test]
+  fn materials_keep_the_draw_order() {
+    for material_first in [true, false] {
+      let mut bowtie =
+        BowTie::headless_with_backend(Box::new(SoftwareBackend::new(2, 2)));
+
+      let mut with_material = material_entity(Some(dissolve_material()), 0);
+      with_material
+        .get_sprite_mut()
+        .set_color_overlay(COLORS::Red.into());
+      let mut without_material = material_entity(None, 0);
+      without_material
+        .get_sprite_mut()
+        .set_color_overlay(COLORS::Green.into());
+
+      let (first, second) = if material_first {
+        (with_material, without_material)
+      } else {
+        (without_material, with_material)
+      };
+      bowtie.load_entity(first);
+      bowtie.load_entity(second);
+      bowtie.step(1);
+
+      let expected = if material_first { GREEN } else { RED };
+      assert_eq!(bowtie.read_pixels().unwrap().get_pixel(0, 0), expected);
+    }
+  }
+
+  #[test]
+  fn sprite_copies_keep_region_and_material() {
+    let mut sprite = Sprite::new(
+      Rectangle::new(-1.0, 1.0, 1.0, 1.0, COLORS::White.into()),
+      Texture::none(),
+    );
+    let region = TextureRegion::new(0.5, 0.0, 0.5, 0.5);
+    sprite.set_texture_region(region);
+    sprite.set_material(Some(dissolve_material()));
+
+    let copy = Sprite::from(&sprite);
+    assert_eq!(copy.get_texture_region(), region);
+    assert_eq!(
+      copy.get_material().map(|material| material.material_id),
+      sprite.get_material().map(|material| material.material_id)
+    );
+  }
+
+  #[test]
+  fn releases_materials_once_unused() {
+    let released = Arc::new(Mutex::new(vec![]));
+    let mut bowtie = BowTie::headless_with_backend(Box::new(MaterialRecorder {
+      released: released.clone(),
+      ..MaterialRecorder::default()
+    }));
+
+    let material = dissolve_material();
+    let material_id = material.material_id;
+    let first =
+      bowtie.load_entity(material_entity(Some(material.to_owned()), 0));
+    let second = bowtie.load_entity(material_entity(Some(material), 0));
+
+    bowtie.step(1);
+    bowtie.unload_entity(first);
+    bowtie.step(1);
+    assert!(released.lock().unwrap().is_empty());
+
+    bowtie.unload_entity(second);
+    bowtie.step(1);
+    assert_eq!(*released.lock().unwrap(), vec![material_id]);
+  }
+
+  #[test]
+  fn validates_material_attributes() {
+    assert!(dissolve_material().validate().is_ok());
+
+    let attributes = [
+      MaterialAttribute::new("too_big", 0, 5),
+      MaterialAttribute::new("too_far", VERTEX_SIZE - 1, 2),
+    ];
+    for attribute in attributes {
+      let material = Material::new("broken", "", "", vec![attribute.to_owned()]);
+      assert!(material.validate().is_err(), "{attribute:?}");
+    }
+  }
+
+  #[test]
+  #[should_panic]
+  fn engine_uniforms_are_reserved() {
+    dissolve_material()
+      .set_uniform("projection", UniformValue::Matrix4([0.0; 16]));
+  }
+
+  #[test]
+  #[should_panic]
+  fn engine_uniform_elements_are_reserved() {
+    dissolve_material().set_uniform("tex_samplers[0]", UniformValue::Int(3));
+  }
+
+  #[test]
+  fn null_backend_has_no_frame() {
+    let mut bowtie = BowTie::headless();
+    bowtie.step(1);
+    assert!(bowtie.read_pixels().is_none());
+  }
+
+  fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+      crc ^= *byte as u32;
+      for _ in 0..8 {
+        crc = if crc & 1 == 1 {
+          (crc >> 1) ^ 0xEDB88320
+        } else {
+          crc >> 1
+        };
+      }
+    }
+    !crc
+  }
+
+  /// Builds a png by hand, for formats `write_png` can't produce.
+  /// `scanlines` needs a filter byte in front of every row (of every
+  /// interlacing pass), and is stored uncompressed.
+  fn raw_png(
+    (width, height): (u32, u32),
+    bit_depth: u8,
+    color_type: u8,
+    interlaced: bool,
+    chunks: &[(&[u8; 4], &[u8])],
+    scanlines: &[u8],
+  ) -> Vec<u8> {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in scanlines {
+      a = (a + *byte as u32) % 65521;
+      b = (b + a) % 65521;
+    }
+    let length = scanlines.len() as u16;
+    let mut zlib = vec![0x78, 0x01, 0x01];
+    zlib.extend_from_slice(&length.to_le_bytes());
+    zlib.extend_from_slice(&(!length).to_le_bytes());
+    zlib.extend_from_slice(scanlines);
+    zlib.extend_from_slice(&((b << 16) | a).to_be_bytes());
+
+    let header = [
+      width.to_be_bytes().as_slice(),
+      height.to_be_bytes().as_slice(),
+      &[bit_depth, color_type, 0, 0, interlaced as u8],
+    ]
+    .concat();
+
+    let mut png = vec![0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+    let all_chunks = [(b"IHDR", header.as_slice())]
+      .into_iter()
+      .chain(chunks.iter().copied())
+      .chain([(b"IDAT", zlib.as_slice()), (b"IEND", [].as_slice())]);
+    for (kind, data) in all_chunks {
+      png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+      let start = png.len();
+      png.extend_from_slice(kind);
+      png.extend_from_slice(data);
+      let crc = crc32(&png[start..]);
+      png.extend_from_slice(&crc.to_be_bytes());
+    }
+    png
+  }
+
+  #[test]
+  fn decodes_every_png_format_to_rgba() {
+    let clear_blue = [0, 0, 255, 0];
+    let gray = [128, 128, 128, 255];
+    let cases: Vec<(&str, Vec<u8>, Vec<[u8; 4]>)> = vec![
+      (
+        "grayscale",
+        raw_png((2, 1), 8, 0, false, &[], &[0, 255, 128]),
+        vec![WHITE, gray],
+      ),
+      (
+        "1-bit grayscale",
+        raw_png((3, 1), 1, 0, false, &[], &[0, 0b1010_0000]),
+        vec![WHITE, [0, 0, 0, 255], WHITE],
+      ),
+      (
+        "grayscale with alpha",
+        raw_png((1, 1), 8, 4, false, &[], &[0, 255, 0]),
+        vec![[255, 255, 255, 0]],
+      ),
+      (
+        "rgb with odd row widths",
+        raw_png(
+          (3, 1),
+          8,
+          2,
+          false,
+          &[],
+          &[0, 255, 0, 0, 0, 255, 0, 0, 0, 255],
+        ),
+        vec![RED, GREEN, BLUE],
+      ),
+      (
+        "indexed with transparency",
+        raw_png(
+          (2, 1),
+          8,
+          3,
+          false,
+          &[(b"PLTE", &[255, 0, 0, 0, 0, 255]), (b"tRNS", &[255, 0])],
+          &[0, 0, 1],
+        ),
+        vec![RED, clear_blue],
+      ),
+      (
+        "16-bit rgba",
+        raw_png(
+          (1, 1),
+          16,
+          6,
+          false,
+          &[],
+          &[0, 0, 0, 0, 0, 255, 255, 0x80, 0],
+        ),
+        vec![[0, 0, 255, 128]],
+      ),
+      (
+        "interlaced",
+        // Adam7 passes 1, 6 and 7 are the only ones covering a 2x2 image
+        raw_png(
+          (2, 2),
+          8,
+          2,
+          true,
+          &[],
+          &[0, 255, 0, 0, 0, 0, 0, 255, 0, 0, 255, 0, 255, 255, 255],
+        ),
+        vec![RED, BLUE, GREEN, WHITE],
+      ),
+    ];
+
+    for (name, png, expected) in cases {
+      let image = ImageData::from_png(png.as_slice())
+        .unwrap_or_else(|error| panic!("{name}: {error}"));
+      let pixels = (0..image.height)
+        .flat_map(|y| (0..image.width).map(move |x| (x, y)))
+        .map(|(x, y)| image.get_pixel(x, y))
+        .collect::<Vec<[u8; 4]>>();
+      assert_eq!(pixels, expected, "{name}");
+    }
+  }
+
+  #[test]
+  fn png_round_trip() {
+    let image = ImageData::new(2, 1, [RED, BLUE].concat());
+    let mut encoded: Vec<u8> = vec![];
+    image.write_png(&mut encoded).unwrap();
+
+    assert_eq!(ImageData::from_png(encoded.as_slice()).unwrap(), image);
+
+    let mut backend = SoftwareBackend::new(2, 2);
+    backend.clear(COLORS::Black.into());
+    assert_eq!(backend.get_framebuffer().get_pixel(1, 1), [0, 0, 0, 255]);
+  }
+}
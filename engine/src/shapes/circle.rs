@@ -0,0 +1,113 @@
+use std::f32::consts::PI;
+
+use crate::{Color, Shape};
+
+use super::shape::get_bounding_texture_corners;
+
+#[derive(Debug, Copy, Clone)]
+/// Circle `Shape`, approximated by a regular polygon with `segments` sides
+///
+/// Like every shape, `x` and `y` are the top left of its bounding box.
+pub struct Circle {
+  pub x: f32,
+  pub y: f32,
+  pub radius: f32,
+  pub segments: u32,
+  pub color: Color,
+  flipped_x: bool,
+  flipped_y: bool,
+}
+
+impl Circle {
+  pub fn new(
+    x: f32,
+    y: f32,
+    radius: f32,
+    segments: u32,
+    color: Color,
+  ) -> Circle {
+    Circle {
+      x,
+      y,
+      radius,
+      segments: segments.max(3),
+      color,
+      flipped_x: false,
+      flipped_y: false,
+    }
+  }
+
+  pub fn get_center(&self) -> (f32, f32) {
+    (self.x + self.radius, self.y - self.radius)
+  }
+}
+
+impl Shape for Circle {
+  fn get_x(&self) -> f32 {
+    self.x
+  }
+
+  fn get_y(&self) -> f32 {
+    self.y
+  }
+
+  fn set_x(&mut self, x: f32) {
+    self.x = x;
+  }
+
+  fn set_y(&mut self, y: f32) {
+    self.y = y;
+  }
+
+  fn get_width(&self) -> f32 {
+    self.radius * 2.0
+  }
+
+  fn get_height(&self) -> f32 {
+    self.radius * 2.0
+  }
+
+  /// Circles stay round, so this sets the diameter
+  fn set_height(&mut self, height: f32) {
+    self.radius = height / 2.0;
+  }
+
+  /// Circles stay round, so this sets the diameter
+  fn set_width(&mut self, width: f32) {
+    self.radius = width / 2.0;
+  }
+
+  fn get_color(&self) -> Color {
+    self.color
+  }
+
+  fn set_color(&mut self, color: Color) {
+    self.color = color;
+  }
+
+  /// Points around the edge, clockwise from the top
+  fn get_coordinate_corners(&self) -> Vec<[f32; 2]> {
+    let (center_x, center_y) = self.get_center();
+    (0..self.segments)
+      .map(|segment| {
+        let angle = segment as f32 / self.segments as f32 * 2.0 * PI;
+        [
+          center_x + self.radius * angle.sin(),
+          center_y + self.radius * angle.cos(),
+        ]
+      })
+      .collect()
+  }
+
+  fn get_texture_corners(&self) -> Vec<[f32; 2]> {
+    get_bounding_texture_corners(self, self.flipped_x, self.flipped_y)
+  }
+
+  fn flip_texture_corners_x(&mut self) {
+    self.flipped_x = !self.flipped_x;
+  }
+
+  fn flip_texture_corners_y(&mut self) {
+    self.flipped_y = !self.flipped_y;
+  }
+}
@@ -1,13 +1,21 @@
 use crate::{Color, Shape};
 
+use super::shape::get_bounding_texture_corners;
+
 #[derive(Debug, Copy, Clone)]
+/// Outline of a rectangle `Shape`
+///
+/// The outline is drawn inwards from the edges, so the shape covers the same
+/// area as a `Rectangle` of the same size.
 pub struct HollowRectangle {
   pub width: f32,
   pub height: f32,
   pub x: f32,
   pub y: f32,
+  pub thickness: f32,
   pub color: Color,
-  texture_corners: [[f32; 2]; 4],
+  flipped_x: bool,
+  flipped_y: bool,
 }
 
 impl HollowRectangle {
@@ -16,6 +24,7 @@ impl HollowRectangle {
     y: f32,
     width: f32,
     height: f32,
+    thickness: f32,
     color: Color,
   ) -> HollowRectangle {
     HollowRectangle {
@@ -23,10 +32,25 @@ impl HollowRectangle {
       y,
       width,
       height,
+      thickness,
       color,
-      texture_corners: [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]],
+      flipped_x: false,
+      flipped_y: false,
     }
   }
+
+  pub fn get_thickness(&self) -> f32 {
+    self.thickness
+  }
+
+  pub fn set_thickness(&mut self, thickness: f32) {
+    self.thickness = thickness;
+  }
+
+  /// Thickness that fits the rectangle, the outline never overlaps itself
+  fn get_stroke(&self) -> f32 {
+    self.thickness.clamp(0.0, self.width.min(self.height) / 2.0)
+  }
 }
 
 impl Shape for HollowRectangle {
@@ -70,19 +94,50 @@ impl Shape for HollowRectangle {
     self.color = color;
   }
 
-  fn get_texture_corners(&self) -> [[f32; 2]; 4] {
-    panic!("HollowRectangle does not support texture_corners")
+  /// Textures stretch over the whole rectangle, the outline shows the
+  /// texture's edges
+  fn get_texture_corners(&self) -> Vec<[f32; 2]> {
+    get_bounding_texture_corners(self, self.flipped_x, self.flipped_y)
   }
 
   fn flip_texture_corners_x(&mut self) {
-    panic!("HollowRectangle does not support texture_corners")
+    self.flipped_x = !self.flipped_x;
   }
 
   fn flip_texture_corners_y(&mut self) {
-    panic!("HollowRectangle does not support texture_corners")
+    self.flipped_y = !self.flipped_y;
   }
 
-  fn get_coordinate_corners(&self) -> [[f32; 2]; 4] {
-    panic!("HollowRectangle does not support texture_corners")
+  /// The outer corners, then the inner corners, both clockwise from the
+  /// top left
+  fn get_coordinate_corners(&self) -> Vec<[f32; 2]> {
+    let stroke = self.get_stroke();
+    let (left, top) = (self.x, self.y);
+    let (right, bottom) = (self.x + self.width, self.y - self.height);
+
+    vec![
+      [left, top],
+      [right, top],
+      [right, bottom],
+      [left, bottom],
+      [left + stroke, top - stroke],
+      [right - stroke, top - stroke],
+      [right - stroke, bottom + stroke],
+      [left + stroke, bottom + stroke],
+    ]
+  }
+
+  /// Two triangles for each side, between its outer and inner edge
+  fn get_elements(&self) -> Vec<i32> {
+    let mut elements = vec![];
+    for side in 0..4 {
+      let next = (side + 1) % 4;
+      let (outer, outer_next) = (side, next);
+      let (inner, inner_next) = (side + 4, next + 4);
+      elements.append(&mut vec![
+        outer, outer_next, inner_next, inner_next, inner, outer,
+      ]);
+    }
+    elements
   }
 }
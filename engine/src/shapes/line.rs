@@ -0,0 +1,182 @@
+use crate::{Color, Shape};
+
+use super::shape::{get_bounding_texture_corners, get_point_bounds};
+
+#[derive(Debug, Clone)]
+/// Line `Shape` through two or more points, e.g. a trajectory
+///
+/// Every segment is drawn as its own quad `thickness` wide, centered on the
+/// segment. Like `Polygon`, the points are kept relative to `x` and `y`, the
+/// top left of their bounding box. The shape's position and size are those
+/// of the box around the quads, so they include the thickness.
+pub struct Line {
+  pub x: f32,
+  pub y: f32,
+  pub thickness: f32,
+  pub color: Color,
+  points: Vec<[f32; 2]>,
+  width: f32,
+  height: f32,
+  flipped_x: bool,
+  flipped_y: bool,
+}
+
+impl Line {
+  pub fn new(points: Vec<[f32; 2]>, thickness: f32, color: Color) -> Line {
+    assert!(points.len() >= 2, "A line needs at least 2 points");
+
+    let (left, top, width, height) = get_point_bounds(&points);
+
+    Line {
+      x: left,
+      y: top,
+      thickness,
+      color,
+      points: points.iter().map(|[x, y]| [x - left, y - top]).collect(),
+      width,
+      height,
+      flipped_x: false,
+      flipped_y: false,
+    }
+  }
+
+  /// Line between two points
+  pub fn between(
+    start: [f32; 2],
+    end: [f32; 2],
+    thickness: f32,
+    color: Color,
+  ) -> Line {
+    Line::new(vec![start, end], thickness, color)
+  }
+
+  /// Points relative to the top left of the bounding box
+  pub fn get_points(&self) -> &Vec<[f32; 2]> {
+    &self.points
+  }
+
+  /// Left, top, width and height of the box around the quads
+  fn get_quad_bounds(&self) -> (f32, f32, f32, f32) {
+    get_point_bounds(&self.get_coordinate_corners())
+  }
+}
+
+impl Shape for Line {
+  fn get_x(&self) -> f32 {
+    self.get_quad_bounds().0
+  }
+
+  fn get_y(&self) -> f32 {
+    self.get_quad_bounds().1
+  }
+
+  fn set_x(&mut self, x: f32) {
+    self.x += x - self.get_x();
+  }
+
+  fn set_y(&mut self, y: f32) {
+    self.y += y - self.get_y();
+  }
+
+  fn get_width(&self) -> f32 {
+    self.get_quad_bounds().2
+  }
+
+  fn get_height(&self) -> f32 {
+    self.get_quad_bounds().3
+  }
+
+  /// Scales the points to the new height, keeping the thickness
+  fn set_height(&mut self, height: f32) {
+    let height = (height - (self.get_height() - self.height)).max(0.0);
+    let scale = if self.height == 0.0 {
+      0.0
+    } else {
+      height / self.height
+    };
+    for point in self.points.iter_mut() {
+      point[1] *= scale;
+    }
+    self.height = height;
+  }
+
+  /// Scales the points to the new width, keeping the thickness
+  fn set_width(&mut self, width: f32) {
+    let width = (width - (self.get_width() - self.width)).max(0.0);
+    let scale = if self.width == 0.0 {
+      0.0
+    } else {
+      width / self.width
+    };
+    for point in self.points.iter_mut() {
+      point[0] *= scale;
+    }
+    self.width = width;
+  }
+
+  fn get_color(&self) -> Color {
+    self.color
+  }
+
+  fn set_color(&mut self, color: Color) {
+    self.color = color;
+  }
+
+  /// Four corners for every segment
+  fn get_coordinate_corners(&self) -> Vec<[f32; 2]> {
+    let mut corners = vec![];
+    for segment in self.points.windows(2) {
+      let [start_x, start_y] = segment[0];
+      let [end_x, end_y] = segment[1];
+
+      let (dx, dy) = (end_x - start_x, end_y - start_y);
+      let length = (dx * dx + dy * dy).sqrt();
+      let (normal_x, normal_y) = if length == 0.0 {
+        (0.0, 0.0)
+      } else {
+        (
+          -dy / length * self.thickness / 2.0,
+          dx / length * self.thickness / 2.0,
+        )
+      };
+
+      let (start_x, start_y) = (self.x + start_x, self.y + start_y);
+      let (end_x, end_y) = (self.x + end_x, self.y + end_y);
+      corners.append(&mut vec![
+        [start_x + normal_x, start_y + normal_y],
+        [end_x + normal_x, end_y + normal_y],
+        [end_x - normal_x, end_y - normal_y],
+        [start_x - normal_x, start_y - normal_y],
+      ]);
+    }
+    corners
+  }
+
+  fn get_texture_corners(&self) -> Vec<[f32; 2]> {
+    get_bounding_texture_corners(self, self.flipped_x, self.flipped_y)
+  }
+
+  fn flip_texture_corners_x(&mut self) {
+    self.flipped_x = !self.flipped_x;
+  }
+
+  fn flip_texture_corners_y(&mut self) {
+    self.flipped_y = !self.flipped_y;
+  }
+
+  fn get_elements(&self) -> Vec<i32> {
+    let mut elements = vec![];
+    for segment in 0..(self.points.len() as i32 - 1) {
+      let corner = segment * 4;
+      elements.append(&mut vec![
+        corner,
+        corner + 1,
+        corner + 2,
+        corner + 2,
+        corner + 3,
+        corner,
+      ]);
+    }
+    elements
+  }
+}
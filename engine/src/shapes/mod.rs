@@ -1,3 +1,8 @@
+pub mod circle;
 pub mod hollow_rectangle;
+pub mod line;
+pub mod polygon;
 pub mod rectangle;
 pub mod shape;
+
+mod test;
@@ -0,0 +1,125 @@
+use crate::{Color, Shape};
+
+use super::shape::{get_bounding_texture_corners, get_point_bounds};
+
+#[derive(Debug, Clone)]
+/// Convex polygon `Shape`
+///
+/// The points are kept relative to the top left of the bounding box, so the
+/// polygon moves around like any other shape.
+/// Concave polygons aren't supported, they get drawn as a triangle fan.
+pub struct Polygon {
+  pub x: f32,
+  pub y: f32,
+  pub color: Color,
+  points: Vec<[f32; 2]>,
+  width: f32,
+  height: f32,
+  flipped_x: bool,
+  flipped_y: bool,
+}
+
+impl Polygon {
+  /// Takes the polygon's points in order, clockwise or counter clockwise
+  pub fn new(points: Vec<[f32; 2]>, color: Color) -> Polygon {
+    assert!(points.len() >= 3, "A polygon needs at least 3 points");
+
+    let (left, top, width, height) = get_point_bounds(&points);
+
+    Polygon {
+      x: left,
+      y: top,
+      color,
+      points: points.iter().map(|[x, y]| [x - left, y - top]).collect(),
+      width,
+      height,
+      flipped_x: false,
+      flipped_y: false,
+    }
+  }
+
+  /// Points relative to the top left of the bounding box
+  pub fn get_points(&self) -> &Vec<[f32; 2]> {
+    &self.points
+  }
+}
+
+impl Shape for Polygon {
+  fn get_x(&self) -> f32 {
+    self.x
+  }
+
+  fn get_y(&self) -> f32 {
+    self.y
+  }
+
+  fn set_x(&mut self, x: f32) {
+    self.x = x;
+  }
+
+  fn set_y(&mut self, y: f32) {
+    self.y = y;
+  }
+
+  fn get_width(&self) -> f32 {
+    self.width
+  }
+
+  fn get_height(&self) -> f32 {
+    self.height
+  }
+
+  /// Scales the points to the new height
+  fn set_height(&mut self, height: f32) {
+    let scale = if self.height == 0.0 {
+      0.0
+    } else {
+      height / self.height
+    };
+    for point in self.points.iter_mut() {
+      point[1] *= scale;
+    }
+    self.height = height;
+  }
+
+  /// Scales the points to the new width
+  fn set_width(&mut self, width: f32) {
+    let scale = if self.width == 0.0 {
+      0.0
+    } else {
+      width / self.width
+    };
+    for point in self.points.iter_mut() {
+      point[0] *= scale;
+    }
+    self.width = width;
+  }
+
+  fn get_color(&self) -> Color {
+    self.color
+  }
+
+  fn set_color(&mut self, color: Color) {
+    self.color = color;
+  }
+
+  fn get_coordinate_corners(&self) -> Vec<[f32; 2]> {
+    self
+      .points
+      .iter()
+      .map(|[x, y]| [self.x + x, self.y + y])
+      .collect()
+  }
+
+  fn get_texture_corners(&self) -> Vec<[f32; 2]> {
+    get_bounding_texture_corners(self, self.flipped_x, self.flipped_y)
+  }
+
+  fn flip_texture_corners_x(&mut self) {
+    self.flipped_x = !self.flipped_x;
+  }
+
+  fn flip_texture_corners_y(&mut self) {
+    self.flipped_y = !self.flipped_y;
+  }
+}
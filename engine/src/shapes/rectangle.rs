@@ -73,8 +73,8 @@ impl Shape for Rectangle {
     self.color = color;
   }
 
-  fn get_texture_corners(&self) -> [[f32; 2]; 4] {
-    self.texture_corners
+  fn get_texture_corners(&self) -> Vec<[f32; 2]> {
+    self.texture_corners.to_vec()
   }
 
   fn flip_texture_corners_x(&mut self) {
@@ -95,12 +95,16 @@ impl Shape for Rectangle {
     self.texture_corners = new_texture_corners;
   }
 
-  fn get_coordinate_corners(&self) -> [[f32; 2]; 4] {
-    [
+  fn get_coordinate_corners(&self) -> Vec<[f32; 2]> {
+    vec![
       [self.x, self.y],
       [self.x + self.width, self.y],
       [self.x + self.width, self.y - self.height],
       [self.x, self.y - self.height],
     ]
   }
+
+  fn get_elements(&self) -> Vec<i32> {
+    vec![0, 1, 2, 2, 3, 0]
+  }
 }
@@ -2,7 +2,7 @@ use std::fmt::Debug;
 
 use crate::general::color::Color;
 
-pub trait Shape: Debug {
+pub trait Shape: Debug + ShapeClone {
   fn get_x(&self) -> f32;
   fn get_y(&self) -> f32;
   fn set_x(&mut self, x: f32);
@@ -13,8 +13,147 @@ pub trait Shape: Debug {
   fn set_width(&mut self, width: f32);
   fn get_color(&self) -> Color;
   fn set_color(&mut self, color: Color);
-  fn get_coordinate_corners(&self) -> [[f32; 2]; 4];
-  fn get_texture_corners(&self) -> [[f32; 2]; 4];
+  fn get_coordinate_corners(&self) -> Vec<[f32; 2]>;
+  /// One texture corner for every coordinate corner
+  fn get_texture_corners(&self) -> Vec<[f32; 2]>;
   fn flip_texture_corners_x(&mut self);
   fn flip_texture_corners_y(&mut self);
+
+  /// Indices into the corners, three per triangle.
+  /// Defaults to a triangle fan, which covers any convex shape.
+  fn get_elements(&self) -> Vec<i32> {
+    let corner_count = self.get_coordinate_corners().len() as i32;
+    let mut elements = vec![];
+    for corner in 1..corner_count - 1 {
+      elements.append(&mut vec![0, corner, corner + 1]);
+    }
+    elements
+  }
+}
+
+/// Lets boxed shapes be cloned, implemented for every `Shape + Clone`
+pub trait ShapeClone {
+  fn box_clone(&self) -> Box<dyn Shape>;
+}
+
+impl<TShape> ShapeClone for TShape
+where
+  TShape: Shape + Clone + 'static,
+{
+  fn box_clone(&self) -> Box<dyn Shape> {
+    Box::new(self.clone())
+  }
+}
+
+impl Clone for Box<dyn Shape> {
+  fn clone(&self) -> Box<dyn Shape> {
+    self.as_ref().box_clone()
+  }
+}
+
+impl Shape for Box<dyn Shape> {
+  fn get_x(&self) -> f32 {
+    self.as_ref().get_x()
+  }
+
+  fn get_y(&self) -> f32 {
+    self.as_ref().get_y()
+  }
+
+  fn set_x(&mut self, x: f32) {
+    self.as_mut().set_x(x)
+  }
+
+  fn set_y(&mut self, y: f32) {
+    self.as_mut().set_y(y)
+  }
+
+  fn get_width(&self) -> f32 {
+    self.as_ref().get_width()
+  }
+
+  fn get_height(&self) -> f32 {
+    self.as_ref().get_height()
+  }
+
+  fn set_height(&mut self, height: f32) {
+    self.as_mut().set_height(height)
+  }
+
+  fn set_width(&mut self, width: f32) {
+    self.as_mut().set_width(width)
+  }
+
+  fn get_color(&self) -> Color {
+    self.as_ref().get_color()
+  }
+
+  fn set_color(&mut self, color: Color) {
+    self.as_mut().set_color(color)
+  }
+
+  fn get_coordinate_corners(&self) -> Vec<[f32; 2]> {
+    self.as_ref().get_coordinate_corners()
+  }
+
+  fn get_texture_corners(&self) -> Vec<[f32; 2]> {
+    self.as_ref().get_texture_corners()
+  }
+
+  fn flip_texture_corners_x(&mut self) {
+    self.as_mut().flip_texture_corners_x()
+  }
+
+  fn flip_texture_corners_y(&mut self) {
+    self.as_mut().flip_texture_corners_y()
+  }
+
+  fn get_elements(&self) -> Vec<i32> {
+    self.as_ref().get_elements()
+  }
+}
+
+/// Texture corners that stretch a texture over the shape's bounding box.
+/// Meant for shapes whose corners aren't just a rectangle's.
+pub(crate) fn get_bounding_texture_corners(
+  shape: &dyn Shape,
+  flipped_x: bool,
+  flipped_y: bool,
+) -> Vec<[f32; 2]> {
+  let (x, y) = (shape.get_x(), shape.get_y());
+  let (width, height) = (shape.get_width(), shape.get_height());
+
+  shape
+    .get_coordinate_corners()
+    .iter()
+    .map(|[corner_x, corner_y]| {
+      let mut tx = if width == 0.0 {
+        0.0
+      } else {
+        (corner_x - x) / width
+      };
+      let mut ty = if height == 0.0 {
+        0.0
+      } else {
+        (y - corner_y) / height
+      };
+      if flipped_x {
+        tx = 1.0 - tx;
+      }
+      if flipped_y {
+        ty = 1.0 - ty;
+      }
+      [tx, ty]
+    })
+    .collect()
+}
+
+/// Left, top, width and height of the box around the points
+pub(crate) fn get_point_bounds(points: &Vec<[f32; 2]>) -> (f32, f32, f32, f32) {
+  let left = points.iter().map(|[x, _]| *x).fold(f32::MAX, f32::min);
+  let right = points.iter().map(|[x, _]| *x).fold(f32::MIN, f32::max);
+  let top = points.iter().map(|[_, y]| *y).fold(f32::MIN, f32::max);
+  let bottom = points.iter().map(|[_, y]| *y).fold(f32::MAX, f32::min);
+
+  (left, top, right - left, top - bottom)
 }
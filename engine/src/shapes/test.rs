@@ -0,0 +1,123 @@
+#[cfg(test)]
+mod shape_tests {
+  use crate::{
+    Circle, HollowRectangle, Line, Polygon, Rectangle, Shape, COLORS,
+  };
+
+  fn assert_close(actual: [f32; 2], expected: [f32; 2]) {
+    assert!(
+      (actual[0] - expected[0]).abs() < 0.0001
+        && (actual[1] - expected[1]).abs() < 0.0001,
+      "{:?} is not {:?}",
+      actual,
+      expected
+    );
+  }
+
+  #[test]
+  fn every_corner_has_texture_corners() {
+    let shapes: Vec<Box<dyn Shape>> = vec![
+      Box::new(Rectangle::new(0.0, 0.0, 0.5, 0.5, COLORS::Red.into())),
+      Box::new(HollowRectangle::new(
+        0.0,
+        0.0,
+        0.5,
+        0.5,
+        0.1,
+        COLORS::Red.into(),
+      )),
+      Box::new(Circle::new(0.0, 0.0, 0.5, 16, COLORS::Red.into())),
+      Box::new(Polygon::new(
+        vec![[0.0, 0.0], [0.5, 0.0], [0.6, -0.3], [0.1, -0.5]],
+        COLORS::Red.into(),
+      )),
+      Box::new(Line::new(
+        vec![[0.0, 0.0], [0.5, 0.0], [0.5, -0.5]],
+        0.1,
+        COLORS::Red.into(),
+      )),
+    ];
+
+    for shape in shapes {
+      let corner_count = shape.get_coordinate_corners().len();
+      assert_eq!(shape.get_texture_corners().len(), corner_count);
+
+      let elements = shape.get_elements();
+      assert_eq!(elements.len() % 3, 0);
+      assert!(elements.iter().all(|e| (*e as usize) < corner_count));
+    }
+  }
+
+  #[test]
+  fn circle_fits_its_bounding_box() {
+    let circle = Circle::new(-0.5, 0.5, 0.25, 4, COLORS::Red.into());
+    assert_eq!(circle.get_width(), 0.5);
+    assert_eq!(circle.get_center(), (-0.25, 0.25));
+
+    let corners = circle.get_coordinate_corners();
+    assert_eq!(corners.len(), 4);
+    assert_close(corners[0], [-0.25, 0.5]);
+    assert_close(corners[1], [0.0, 0.25]);
+    assert_close(corners[2], [-0.25, 0.0]);
+    assert_close(corners[3], [-0.5, 0.25]);
+    assert_eq!(circle.get_elements(), vec![0, 1, 2, 0, 2, 3]);
+  }
+
+  #[test]
+  fn polygon_moves_with_its_bounding_box() {
+    let mut triangle = Polygon::new(
+      vec![[0.0, 0.5], [0.5, -0.5], [-0.5, -0.5]],
+      COLORS::Red.into(),
+    );
+    assert_eq!((triangle.get_x(), triangle.get_y()), (-0.5, 0.5));
+    assert_eq!((triangle.get_width(), triangle.get_height()), (1.0, 1.0));
+
+    triangle.set_x(0.0);
+    triangle.set_y(0.0);
+    assert_eq!(
+      triangle.get_coordinate_corners(),
+      vec![[0.5, 0.0], [1.0, -1.0], [0.0, -1.0]]
+    );
+    assert_eq!(
+      triangle.get_texture_corners(),
+      vec![[0.5, 0.0], [1.0, 1.0], [0.0, 1.0]]
+    );
+  }
+
+  #[test]
+  fn line_segments_are_quads() {
+    let line = Line::new(
+      vec![[0.0, 0.0], [1.0, 0.0], [1.0, -1.0]],
+      0.2,
+      COLORS::Red.into(),
+    );
+
+    let corners = line.get_coordinate_corners();
+    assert_eq!(corners.len(), 8);
+    assert_close(corners[0], [0.0, 0.1]);
+    assert_close(corners[2], [1.0, -0.1]);
+    assert_close(corners[4], [1.1, 0.0]);
+    assert_close(corners[6], [0.9, -1.0]);
+    assert_eq!(line.get_elements().len(), 12);
+  }
+
+  #[test]
+  fn line_bounds_include_thickness() {
+    let mut line =
+      Line::between([0.0, 0.0], [1.0, 0.0], 0.2, COLORS::Red.into());
+    assert_close([line.get_x(), line.get_y()], [0.0, 0.1]);
+    assert_close([line.get_width(), line.get_height()], [1.0, 0.2]);
+
+    let texture_corners = line.get_texture_corners();
+    assert_close(texture_corners[0], [0.0, 0.0]);
+    assert_close(texture_corners[2], [1.0, 1.0]);
+
+    line.set_x(-0.5);
+    line.set_y(0.5);
+    assert_close(line.get_coordinate_corners()[0], [-0.5, 0.5]);
+    assert_close(line.get_coordinate_corners()[2], [0.5, 0.3]);
+
+    line.set_width(0.5);
+    assert_close([line.get_width(), line.get_height()], [0.5, 0.2]);
+  }
+}
@@ -1,2 +1,3 @@
 pub mod drawable;
 pub mod sprite;
+pub mod sprite_sheet;
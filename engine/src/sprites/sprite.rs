@@ -1,14 +1,15 @@
 use crate::{
-  general::{color::Color, direction::Direction},
-  gl_utils::{
-    gl_texture::LoadableTexture, gl_texture::Texture,
-    shader_creator::ShaderProgram,
-  },
+  general::{bounds::Bounds, color::Color, direction::Direction},
+  gl_utils::gl_texture::Texture,
   math::matrix::{IdentityMatrix, Matrix},
+  rendering::material::Material,
   shapes::shape::Shape,
 };
 
-use super::drawable::Drawable;
+use super::{
+  drawable::Drawable,
+  sprite_sheet::{SpriteSheet, TextureRegion},
+};
 use std::marker::PhantomData;
 
 #[derive(Debug, Clone)]
@@ -22,6 +23,13 @@ where
   pub texture: Texture,
   phantom: PhantomData<&'a TShape>,
   transformation: Matrix<f32>,
+  bounds: Option<Bounds>,
+  texture_region: TextureRegion,
+  /// Part of the texture the sprite's image was packed into, if the texture
+  /// is an atlas page
+  atlas_region: Option<TextureRegion>,
+  /// Drawn with the built-in shaders if there is none
+  material: Option<Material>,
 }
 
 impl<'a, TShape: 'a> Sprite<'a, TShape>
@@ -35,6 +43,10 @@ where
       texture,
       phantom: PhantomData,
       transformation: Matrix::<f32>::generate_identity(4),
+      bounds: None,
+      texture_region: TextureRegion::full(),
+      atlas_region: None,
+      material: None,
     }
   }
 
@@ -49,43 +61,140 @@ where
       texture,
       phantom: PhantomData,
       transformation: trans,
+      bounds: None,
+      texture_region: TextureRegion::full(),
+      atlas_region: None,
+      material: None,
     }
   }
 
-  pub fn move_sprite(&mut self, direction: Direction, amount: f32) {
-    match direction {
-      Direction::Right => {
-        self.move_right(amount);
-      }
-      Direction::Left => {
-        self.move_left(amount);
-      }
-      Direction::Up => {
-        self.move_up(amount);
-      }
-      Direction::Down => {
-        self.move_down(amount);
-      }
-      Direction::UpRight => {
-        self.move_right(amount);
-        self.move_up(amount);
-      }
-      Direction::UpLeft => {
-        self.move_left(amount);
-        self.move_up(amount);
-      }
-      Direction::DownRight => {
-        self.move_right(amount);
-        self.move_down(amount);
-      }
-      Direction::DownLeft => {
-        self.move_left(amount);
-        self.move_down(amount);
-      }
-      _ => {}
+  /// Sprite showing one of the sheet's frames.
+  /// Panics if the sheet has no such frame.
+  pub fn from_sheet(
+    shape: TShape,
+    sheet: &SpriteSheet,
+    frame: usize,
+  ) -> Sprite<'a, TShape> {
+    let mut sprite = Sprite::new(shape, sheet.get_texture().to_owned());
+    sprite.set_texture_region(
+      sheet
+        .get_frame(frame)
+        .expect("Sprite sheet frame out of range"),
+    );
+    sprite
+  }
+
+  /// Same sprite with its shape behind a trait object, so sprites of
+  /// different shapes can be stored together
+  pub fn boxed(self) -> Sprite<'a, Box<dyn Shape>>
+  where
+    TShape: 'static,
+  {
+    Sprite {
+      shape: Box::new(self.shape),
+      name: self.name,
+      texture: self.texture,
+      phantom: PhantomData,
+      transformation: self.transformation,
+      bounds: self.bounds,
+      texture_region: self.texture_region,
+      atlas_region: self.atlas_region,
+      material: self.material,
     }
   }
 
+  /// Part of the texture the shape's texture corners map to, the whole
+  /// texture by default
+  pub fn set_texture_region(&mut self, region: TextureRegion) {
+    self.texture_region = region;
+  }
+
+  pub fn get_texture_region(&self) -> TextureRegion {
+    self.texture_region
+  }
+
+  /// Shows a region of the sprite's own image, e.g. a sprite sheet frame.
+  /// Unlike `set_texture_region`, the region is mapped into the atlas page
+  /// if the texture was packed.
+  pub fn set_image_region(&mut self, region: TextureRegion) {
+    self.texture_region = match &self.atlas_region {
+      Some(atlas_region) => atlas_region.map_region(&region),
+      None => region,
+    };
+  }
+
+  /// Points the sprite at an atlas page its image was packed into at
+  /// `region`, see `TextureAtlas::apply`
+  pub fn set_atlas_page(&mut self, page: Texture, region: TextureRegion) {
+    self.texture_region = region.map_region(&self.texture_region);
+    self.atlas_region = Some(region);
+    self.texture = page;
+  }
+
+  /// Part of the atlas page the sprite's image was packed into, `None` if
+  /// its texture isn't an atlas page
+  pub fn get_atlas_region(&self) -> Option<TextureRegion> {
+    self.atlas_region
+  }
+
+  /// Shaders the sprite is drawn with, the built-in ones for `None`
+  pub fn set_material(&mut self, material: Option<Material>) {
+    self.material = material;
+  }
+
+  pub fn get_material(&self) -> Option<&Material> {
+    self.material.as_ref()
+  }
+
+  /// The sprite's copy of its material, e.g. to change its uniforms
+  pub fn get_material_mut(&mut self) -> Option<&mut Material> {
+    self.material.as_mut()
+  }
+
+  pub fn get_shape(&self) -> &TShape {
+    &self.shape
+  }
+
+  pub fn get_shape_mut(&mut self) -> &mut TShape {
+    &mut self.shape
+  }
+
+  /// Moves the sprite and returns the edges of its bounds it went past,
+  /// `Direction::Stationary` if none
+  pub fn move_sprite(&mut self, direction: Direction, amount: f32) -> Direction {
+    let (x, y) = direction.as_vector();
+    self.shape.set_x(self.shape.get_x() + x * amount);
+    self.shape.set_y(self.shape.get_y() + y * amount);
+
+    match self.bounds {
+      Some(bounds) => self.confine_to(&bounds),
+      None => Direction::Stationary,
+    }
+  }
+
+  /// Area the sprite's `move_*` functions keep it in, none by default
+  pub fn set_bounds(&mut self, bounds: Option<Bounds>) {
+    self.bounds = bounds;
+  }
+
+  pub fn get_bounds(&self) -> Option<Bounds> {
+    self.bounds
+  }
+
+  /// Applies `bounds` to the sprite's current position.
+  /// Returns the edges it went past, see `Bounds::confine`.
+  pub fn confine_to(&mut self, bounds: &Bounds) -> Direction {
+    let (x, y, hit) = bounds.confine(
+      self.shape.get_x(),
+      self.shape.get_y(),
+      self.shape.get_width(),
+      self.shape.get_height(),
+    );
+    self.shape.set_x(x);
+    self.shape.set_y(y);
+    hit
+  }
+
   pub fn flip_vertical(&mut self) {
     self.shape.flip_texture_corners_y()
   }
@@ -127,56 +236,24 @@ where
     self.shape.get_width()
   }
 
-  pub fn move_up(&mut self, amount: f32) -> bool {
-    let new_amount = self.shape.get_y() + amount;
-
-    if new_amount >= 0.8 {
-      return false;
-    }
-
-    self.shape.set_y(new_amount);
-
-    return true;
+  pub fn move_up(&mut self, amount: f32) -> Direction {
+    self.move_sprite(Direction::Up, amount)
   }
 
-  pub fn move_down(&mut self, amount: f32) -> bool {
-    let new_amount = self.shape.get_y() - amount;
-
-    if new_amount <= -0.7 {
-      return false;
-    }
-
-    self.shape.set_y(new_amount);
-
-    return true;
+  pub fn move_down(&mut self, amount: f32) -> Direction {
+    self.move_sprite(Direction::Down, amount)
   }
 
-  pub fn move_right(&mut self, amount: f32) -> bool {
-    let new_amount = self.shape.get_x() + amount;
-
-    if new_amount > 0.7 {
-      return false;
-    }
-
-    self.shape.set_x(new_amount);
-
-    return true;
+  pub fn move_right(&mut self, amount: f32) -> Direction {
+    self.move_sprite(Direction::Right, amount)
   }
 
   pub fn set_color_overlay(&mut self, color: Color) {
     self.shape.set_color(color);
   }
 
-  pub fn move_left(&mut self, amount: f32) -> bool {
-    let new_amount = self.shape.get_x() - amount;
-
-    if new_amount <= -1.0 {
-      return false;
-    }
-
-    self.shape.set_x(new_amount);
-
-    return true;
+  pub fn move_left(&mut self, amount: f32) -> Direction {
+    self.move_sprite(Direction::Left, amount)
   }
 }
 
@@ -184,16 +261,12 @@ impl<'a, TShape> Drawable<'a> for Sprite<'a, TShape>
 where
   TShape: Shape + 'a,
 {
-  fn set_texture_uniform(&'a self, program: &ShaderProgram) -> () {
-    self.texture.set_uniform(program);
-  }
-
   fn get_corner_count(&'a self) -> i32 {
     self.shape.get_coordinate_corners().len() as i32
   }
 
   fn get_elements(&self) -> Vec<i32> {
-    return vec![0, 1, 2, 2, 3, 0];
+    self.shape.get_elements()
   }
 
   fn get_vertices(&self) -> Vec<f32> {
@@ -204,7 +277,7 @@ where
     let coordinate_corners = &self.shape.get_coordinate_corners();
     let texture_corners = &self.shape.get_texture_corners();
 
-    for i in 0..4 {
+    for i in 0..coordinate_corners.len() {
       // X, Y
       let [x, y] = coordinate_corners[i];
       vertices.push(x);
@@ -217,7 +290,7 @@ where
       vertices.push(shape.get_color().a);
 
       // Texture Cords
-      let [tx, ty] = texture_corners[i];
+      let [tx, ty] = self.texture_region.map_corner(texture_corners[i]);
       vertices.push(tx);
       vertices.push(ty);
 
@@ -235,7 +308,10 @@ impl<'a, TShape> From<&Sprite<'a, TShape>> for Sprite<'a, TShape>
 where
   TShape: Shape + Clone,
 {
+  /*
+   * Copies the sprite, with its region, bounds, transformation and material
+   */
   fn from(sprite_ref: &Sprite<'a, TShape>) -> Self {
-    Sprite::new(sprite_ref.shape.to_owned(), sprite_ref.texture.to_owned())
+    sprite_ref.to_owned()
   }
 }
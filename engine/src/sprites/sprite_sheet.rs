@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+
+use crate::gl_utils::gl_texture::Texture;
+
+/// Part of a texture, in texture coordinates from 0.0 to 1.0.
+/// Like texture corners, `(0.0, 0.0)` is the texture's top left corner.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TextureRegion {
+  pub x: f32,
+  pub y: f32,
+  pub width: f32,
+  pub height: f32,
+}
+
+impl TextureRegion {
+  pub fn new(x: f32, y: f32, width: f32, height: f32) -> TextureRegion {
+    TextureRegion {
+      x,
+      y,
+      width,
+      height,
+    }
+  }
+
+  /// Region covering the whole texture
+  pub fn full() -> TextureRegion {
+    TextureRegion::new(0.0, 0.0, 1.0, 1.0)
+  }
+
+  /// Region of a `image_width` by `image_height` image, given in pixels
+  pub fn from_pixels(
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    image_width: u32,
+    image_height: u32,
+  ) -> TextureRegion {
+    let (image_width, image_height) = (image_width as f32, image_height as f32);
+    TextureRegion::new(
+      x as f32 / image_width,
+      y as f32 / image_height,
+      width as f32 / image_width,
+      height as f32 / image_height,
+    )
+  }
+
+  /// Maps a texture corner of the whole texture into this region
+  pub fn map_corner(&self, corner: [f32; 2]) -> [f32; 2] {
+    let [tx, ty] = corner;
+    [self.x + tx * self.width, self.y + ty * self.height]
+  }
+
+  /// Maps a region of the whole texture into this region, e.g. a sprite
+  /// sheet frame into the part of an atlas the sheet was packed into
+  pub fn map_region(&self, region: &TextureRegion) -> TextureRegion {
+    let [x, y] = self.map_corner([region.x, region.y]);
+    TextureRegion::new(
+      x,
+      y,
+      region.width * self.width,
+      region.height * self.height,
+    )
+  }
+}
+
+impl Default for TextureRegion {
+  fn default() -> Self {
+    TextureRegion::full()
+  }
+}
+
+/// Texture holding several frames, e.g. every step of a walk cycle
+///
+/// Frames are looked up by index, in the order they were added, or by name.
+#[derive(Debug, Clone)]
+pub struct SpriteSheet {
+  texture: Texture,
+  frames: Vec<TextureRegion>,
+  frame_names: HashMap<String, usize>,
+}
+
+impl SpriteSheet {
+  /// Sprite sheet without any frames yet, see `add_frame`
+  pub fn new(texture: Texture) -> SpriteSheet {
+    SpriteSheet {
+      texture,
+      frames: vec![],
+      frame_names: HashMap::new(),
+    }
+  }
+
+  /// Slices the texture into a grid of equally sized frames, numbered left
+  /// to right and then top to bottom
+  pub fn grid(texture: Texture, columns: u32, rows: u32) -> SpriteSheet {
+    let mut sheet = SpriteSheet::new(texture);
+    let (width, height) = (1.0 / columns as f32, 1.0 / rows as f32);
+
+    for row in 0..rows {
+      for column in 0..columns {
+        sheet.add_frame(TextureRegion::new(
+          column as f32 * width,
+          row as f32 * height,
+          width,
+          height,
+        ));
+      }
+    }
+    sheet
+  }
+
+  /// Adds a frame and returns its index
+  pub fn add_frame(&mut self, region: TextureRegion) -> usize {
+    self.frames.push(region);
+    self.frames.len() - 1
+  }
+
+  /// Adds a frame that can also be looked up by name
+  pub fn add_named_frame(&mut self, name: &str, region: TextureRegion) -> usize {
+    let index = self.add_frame(region);
+    self.frame_names.insert(String::from(name), index);
+    index
+  }
+
+  /// Gives an existing frame a name
+  pub fn name_frame(&mut self, name: &str, index: usize) {
+    self.frame_names.insert(String::from(name), index);
+  }
+
+  pub fn get_texture(&self) -> &Texture {
+    &self.texture
+  }
+
+  pub fn get_frame(&self, index: usize) -> Option<TextureRegion> {
+    self.frames.get(index).copied()
+  }
+
+  pub fn get_frame_index(&self, name: &str) -> Option<usize> {
+    self.frame_names.get(name).copied()
+  }
+
+  pub fn get_named_frame(&self, name: &str) -> Option<TextureRegion> {
+    self.get_frame(self.get_frame_index(name)?)
+  }
+
+  pub fn get_frame_count(&self) -> usize {
+    self.frames.len()
+  }
+}
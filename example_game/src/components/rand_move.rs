@@ -26,7 +26,7 @@ impl<'a> RandMove<'a> {
   pub fn component(&'a self) -> StandardComponent<'a> {
     let direction = Direction::from(rand::thread_rng().gen_range(0..8));
     let magnitude = rand::thread_rng().gen_range(0.0..0.03);
-    StandardComponent::new(Arc::new(move |entity, _store| {
+    StandardComponent::new(Arc::new(move |entity, _store, _context| {
       entity.move_in_direction(direction, magnitude);
     }), "rand_move", HashMap::new())
     .to_owned()
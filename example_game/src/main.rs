@@ -10,14 +10,14 @@ use rand::Rng;
 use bowtie::{
   init_debug_callback, math,
   premade_components::{CollisionComponent, GravityComponent, KeyboardMoveComponent},
-  BowTie, Direction, Entity, Message, Rectangle, Sprite, StandardComponent,
+  Bounds, BoundsBehavior, BowTie, Direction, Entity, Message, Rectangle, Sprite, StandardComponent,
   StandardEntity, Texture, TextureOptions, COLORS, WindowMode, WindowConfig, glfw
 };
 
 
 fn main() {
 
-  let mut collision = CollisionComponent::new();
+  let collision = CollisionComponent::new();
   let rand_move1 = RandMove::new();
   let mut gravity = GravityComponent::new(0.002);
   let keyboard_move = KeyboardMoveComponent::new(0.02, 0.0, 0.4);
@@ -28,6 +28,9 @@ fn main() {
   let keyboard_move_comp = keyboard_move.component();
 
   let mut bowtie = BowTie::new();
+  bowtie.set_asset_root(env!("CARGO_MANIFEST_DIR"));
+  bowtie.set_hot_reload(cfg!(debug_assertions));
+  bowtie.set_bounds(Some(Bounds::screen(BoundsBehavior::Clamp)));
   bowtie.create_window(WindowConfig { width: 1000, height: 800, name: String::from("rust game engine"), mode: WindowMode::Windowed });
 
   let en_texture = Texture::new("witch", TextureOptions::default());
@@ -43,7 +46,8 @@ fn main() {
 
   bowtie.prep_for_render();
 
-  //TODO: Make hollow rectangle
+  // Separate rectangles instead of one HollowRectangle, so each wall only
+  // collides along its own edge of the screen
   let line_thickness = 0.01;
   let line_vectors = [
     (-1.0, 1.0, 2.0, line_thickness),
@@ -53,18 +57,25 @@ fn main() {
   ];
   for line in line_vectors {
     let (x, y, w, h) = line;
-    let created_line = bowtie.load_entity(StandardEntity::new(
+    let mut created_line = StandardEntity::new(
       Sprite::new(
         Rectangle::new(x, y, w, h, COLORS::Red.into()),
         Texture::none(),
       ),
       0.0,
-    ));
+    );
     created_line.load_components(collision_comp.to_owned());
+    bowtie.load_entity(created_line);
   }
 
   while !bowtie.should_close() {
     bowtie.tick();
+    for (image_name, error) in bowtie.take_reload_errors() {
+      eprintln!("Could not reload {image_name}: {error}");
+    }
+    for (material_name, error) in bowtie.take_material_errors() {
+      eprintln!("Could not compile material {material_name}: {error}");
+    }
     let events = bowtie.flush_events();
 
     for event in events {
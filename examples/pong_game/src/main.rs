@@ -10,13 +10,13 @@ use bowtie::{
   premade_components::{
     CollisionComponent, GravityComponent, KeyboardMoveComponent,
   },
-  BowTie, Direction, Entity, Message, Rectangle, Sprite, StandardComponent,
-  StandardEntity, Texture, TextureOptions, Value, WindowConfig, WindowMode,
-  COLORS,
+  Bounds, BoundsBehavior, BowTie, Circle, Direction, Entity, Message, Rectangle,
+  ScalingPolicy, Sprite, StandardComponent, StandardEntity, Texture,
+  TextureOptions, Value, WindowConfig, WindowMode, COLORS,
 };
 
 fn main() {
-  let mut collision = CollisionComponent::new();
+  let collision = CollisionComponent::new();
   let keyboard_move = KeyboardMoveComponent::new(0.02, 0.0, 0.4);
   let keyboard_move_comp = keyboard_move.component();
   let collision_comp = collision.component();
@@ -28,6 +28,8 @@ fn main() {
     name: String::from("rust game engine"),
     mode: WindowMode::Windowed,
   });
+  bowtie.set_scaling_policy(ScalingPolicy::Letterbox);
+  bowtie.set_bounds(Some(Bounds::screen(BoundsBehavior::Clamp)));
 
   let mut player_rect = StandardEntity::new(
     Sprite::new(
@@ -47,14 +49,14 @@ fn main() {
 
   let mut ball = StandardEntity::new(
     Sprite::new(
-      Rectangle::new(0.0, -0.2, 0.1, 0.1, COLORS::Violet.into()),
+      Circle::new(0.0, -0.2, 0.05, 24, COLORS::Violet.into()),
       Texture::none(),
     ),
     0.0,
   );
 
   let move_till_collide = StandardComponent::new(
-    Arc::new(|entity, store| {
+    Arc::new(|entity, store, _context| {
       let key = String::from("direction");
       let mut store_locked = store.lock().unwrap();
       let current_direction_val = store_locked.get_mut(&key).unwrap();
@@ -62,39 +64,43 @@ fn main() {
       if let Value::Number(direction_num) = current_direction_val {
         let mut cur_direction = Direction::from(direction_num.clone());
 
-        let comp = entity
-          .get_component(CollisionComponent::get_name().as_str())
-          .to_owned();
+        let collision_direction =
+          CollisionComponent::get_entity_collision_direction(entity);
 
-        if let Some(entity_collision_comp) = comp {
+        if collision_direction != Direction::Stationary {
           let (cur_x, cur_y) = cur_direction.as_vector();
+          let mut new_x: f32 = cur_x;
+          let mut new_y: f32 = cur_y;
+          let (col_x, col_y) = collision_direction.as_vector();
 
-          let collision_store =
-            entity_collision_comp.get_store().lock().unwrap();
-          let entity_ptr: *const StandardEntity = entity;
-          let entity_id = format!("{:?}", entity_ptr);
+          if col_x != 0.0 {
+            new_x *= -1.0;
+          }
 
-          if let Some(num) = collision_store.get(&entity_id) {
-            if let Value::Number(dir_num) = num {
-              let mut new_x: f32 = cur_x;
-              let mut new_y: f32 = cur_y;
-              let (col_x, col_y) = Direction::from(dir_num.clone()).as_vector();
+          if col_y != 0.0 {
+            new_y *= -1.0;
+          }
 
-              if col_x != 0.0 {
-                new_x *= -1.0;
-              }
+          cur_direction = Direction::from_vector((new_x, new_y));
+          *current_direction_val = Value::Number(cur_direction.into());
+        }
 
-              if col_y != 0.0 {
-                new_y *= -1.0;
-              }
+        let hit = entity.move_in_direction(cur_direction, 0.02);
+        let (hit_x, hit_y) = hit.as_vector();
 
-              cur_direction = Direction::from_vector((new_x, new_y));
-              *current_direction_val = Value::Number(cur_direction.into());
-            }
-          }
+        // Bounce off the top and bottom, score when going past a paddle
+        if hit_y != 0.0 {
+          let (cur_x, cur_y) = cur_direction.as_vector();
+          cur_direction = Direction::from_vector((cur_x, -cur_y));
+          *current_direction_val = Value::Number(cur_direction.into());
         }
 
-        entity.move_in_direction(cur_direction, 0.02);
+        if hit_x != 0.0 {
+          let scorer = if hit_x > 0.0 { "player" } else { "enemy" };
+          println!("Point for the {scorer}");
+          entity.set_x(-0.05);
+          entity.set_y(0.05);
+        }
       }
     }),
     "move_till_collide",
@@ -105,29 +111,24 @@ fn main() {
   );
 
   let follow_ball_comp = StandardComponent::new(
-    Arc::new(|entity, store| {
-
-      let key = String::from("direction");
-      let mut store_locked = store.lock().unwrap();
-      let current_direction_val = store_locked.get_mut(&key).unwrap();
-
-      if let Value::Number(direction_num) = current_direction_val {
-        entity.move_in_direction(Direction::from(direction_num.to_owned()), 0.05);
-
-        if entity.get_y() - entity.get_height() < -0.9 {
-          *current_direction_val = Value::Number(Direction::Up.into());
-        } else if entity.get_y() > 0.9 {
-          *current_direction_val = Value::Number(Direction::Down.into());
-        } 
-
+    Arc::new(|entity, _store, context| {
+      let ball = match context.query().with_tag("ball").first() {
+        Some(ball) => ball,
+        None => return,
+      };
+
+      let ball_center = ball.get_y() - ball.get_height() / 2.0;
+      let center = entity.get_y() - entity.get_height() / 2.0;
+      let distance = ball_center - center;
+
+      if distance > 0.01 {
+        entity.move_in_direction(Direction::Up, distance.min(0.03));
+      } else if distance < -0.01 {
+        entity.move_in_direction(Direction::Down, (-distance).min(0.03));
       }
-
     }),
     "follow_ball",
-    HashMap::from([(
-      String::from("direction"),
-      Value::Number(Direction::Up.into()),
-    )]),
+    HashMap::new(),
   );
 
   player_rect.load_components(keyboard_move_comp);
@@ -135,6 +136,7 @@ fn main() {
   player_rect.load_components(collision_comp.to_owned());
   enemy_rect.load_components(collision_comp.to_owned());
   enemy_rect.load_components(follow_ball_comp.to_owned());
+  ball.add_tag("ball");
   ball.load_components(collision_comp.to_owned());
   ball.load_components(move_till_collide);
 